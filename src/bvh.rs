@@ -0,0 +1,158 @@
+use crate::math::{
+    bounding_box::BoundingBox,
+    ray::Ray,
+};
+
+const LEAF_SIZE: usize = 4;
+
+enum Node {
+    Leaf { bbox: BoundingBox, start: usize, count: usize },
+    Branch { bbox: BoundingBox, left: usize, right: usize },
+}
+
+impl Node {
+    fn bbox(&self) -> &BoundingBox {
+        match self {
+            Node::Leaf { bbox, .. } => bbox,
+            Node::Branch { bbox, .. } => bbox,
+        }
+    }
+}
+
+/// Bounding volume hierarchy over primitives only known by their index and box
+pub struct Bvh {
+    nodes: Vec<Node>,
+    items: Vec<usize>,
+    unbounded: Vec<usize>,
+}
+
+impl Bvh {
+    /// ### Brief
+    /// Build the hierarchy, primitives without bounding box are kept aside
+    /// and tested against every ray
+    ///
+    /// ### Params
+    /// **boxes** World space bounding box of each primitive
+    pub fn new(boxes: &[Option<BoundingBox>]) -> Self {
+        let (mut items, mut unbounded) = (Vec::new(), Vec::new());
+
+        for (id, bbox) in boxes.iter().enumerate() {
+            match bbox {
+                Some(_) => items.push(id),
+                None => unbounded.push(id),
+            }
+        }
+
+        let mut bvh = Self { nodes: Vec::new(), items, unbounded };
+        if !bvh.items.is_empty() {
+            let boxes: Vec<BoundingBox> = boxes.iter().flatten().copied().collect();
+            let mut order: Vec<usize> = (0..boxes.len()).collect();
+
+            bvh.build(&boxes, &mut order, 0);
+            bvh.items = order.into_iter().map(|id| bvh.items[id]).collect();
+        }
+
+        bvh
+    }
+
+    /// Bounding box of every bounded primitives
+    pub fn bounds(&self) -> Option<BoundingBox> {
+        self.nodes.first().map(|node| *node.bbox())
+    }
+
+    fn build(&mut self, boxes: &[BoundingBox], order: &mut [usize], offset: usize) -> usize {
+        let bbox = order.iter().fold(BoundingBox::empty(), |bbox, id| bbox.union(&boxes[*id]));
+        let node_id = self.nodes.len();
+
+        let centroids = order.iter().fold(BoundingBox::empty(), |bbox, id| bbox.grow(&boxes[*id].centroid()));
+        let extent = centroids.extent();
+
+        let axis = if extent.x >= extent.y && extent.x >= extent.z {
+            0
+        } else if extent.y >= extent.z {
+            1
+        } else {
+            2
+        };
+
+        let axis_extent = [extent.x, extent.y, extent.z][axis];
+        if order.len() <= LEAF_SIZE || axis_extent <= 0.0 {
+            self.nodes.push(Node::Leaf { bbox, start: offset, count: order.len() });
+            return node_id;
+        }
+
+        let key = |id: &usize| {
+            let centroid = boxes[*id].centroid();
+            [centroid.x, centroid.y, centroid.z][axis]
+        };
+
+        order.sort_unstable_by(|a, b| key(a).total_cmp(&key(b)));
+        let mid = order.len() / 2;
+
+        // reserve the slot, children are pushed after their parent
+        self.nodes.push(Node::Leaf { bbox, start: offset, count: 0 });
+        let (left_order, right_order) = order.split_at_mut(mid);
+        let left = self.build(boxes, left_order, offset);
+        let right = self.build(boxes, right_order, offset + mid);
+
+        self.nodes[node_id] = Node::Branch { bbox, left, right };
+        node_id
+    }
+
+    /// ### Brief
+    /// Find the closest primitive hit by **ray**
+    ///
+    /// ### Params
+    /// **ray** Ray to trace
+    /// **intersect** Return the ray coefficient of the impact with the primitive
+    /// at the given index, along with any data the caller want back
+    ///
+    /// ### Return
+    /// Index of the closest primitive and the data of its impact
+    pub fn closer<T, F>(&self, ray: &Ray, mut intersect: F) -> Option<(usize, T)>
+    where F: FnMut(usize) -> Option<(f32, T)> {
+        let mut hit = None;
+        let mut dist = f32::INFINITY;
+
+        let mut test = |id: usize, dist: &mut f32, hit: &mut Option<(usize, T)>| {
+            if let Some((new_dist, data)) = intersect(id) {
+                if new_dist < *dist {
+                    *dist = new_dist;
+                    *hit = Some((id, data));
+                }
+            }
+        };
+
+        for &id in self.unbounded.iter() {
+            test(id, &mut dist, &mut hit);
+        }
+
+        let mut stack = Vec::with_capacity(64);
+        if !self.nodes.is_empty() {
+            stack.push(0);
+        }
+
+        while let Some(node_id) = stack.pop() {
+            let node = &self.nodes[node_id];
+
+            match node.bbox().intersect(ray) {
+                Some((near, _far)) if near < dist => (),
+                _ => continue,
+            }
+
+            match *node {
+                Node::Leaf { start, count, .. } => {
+                    for &id in self.items[start..start + count].iter() {
+                        test(id, &mut dist, &mut hit);
+                    }
+                }
+                Node::Branch { left, right, .. } => {
+                    stack.push(right);
+                    stack.push(left);
+                }
+            }
+        }
+
+        hit
+    }
+}
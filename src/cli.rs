@@ -0,0 +1,189 @@
+use rustracer::object::camera::Focal;
+use rustracer::{Camera, Config, Scene};
+
+pub const USAGE: &str = "\
+usage: rustracer [render|validate|info] <scene.json> [options]
+
+commands:
+    render      render the scene, the default when the command is omitted
+    validate    only check that the scene file can be loaded
+    info        print a summary of the scene and of its render settings
+
+options, overriding the scene file:
+    -o, --output <file>         image to write
+    -r, --resolution <WxH>      size of the image in pixels
+    -t, --threads <count>       number of render threads
+    -d, --depth <count>         maximum number of bounces
+    -s, --samples <count>       samples per pixel
+    -f, --flags <f1,f2,...>     camera flags among ANTI_ALIASING and NO_SHADOW, empty for none
+    -h, --help                  print this message";
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Command {
+    Render,
+    Validate,
+    Info,
+}
+
+/// Settings given on the command line, they take precedence over the scene file
+#[derive(Default, Debug)]
+pub struct Overrides {
+    pub output: Option<String>,
+    pub resolution: Option<(usize, usize)>,
+    pub threads: Option<usize>,
+    pub depth: Option<usize>,
+    pub samples: Option<usize>,
+    pub flags: Option<u8>,
+}
+
+#[derive(Debug)]
+pub struct Cli {
+    pub command: Command,
+    pub scene: String,
+    pub overrides: Overrides,
+}
+
+/// Outcome of the command line parsing which isn't a command to run
+#[derive(Debug)]
+pub enum CliError {
+    /// The help was asked for
+    Help,
+    /// The arguments are wrong, the message tells why
+    Usage(String),
+}
+
+/// ### Brief
+/// Read the arguments of the program, without the program name
+pub fn parse<I: Iterator<Item = String>>(mut args: I) -> Result<Cli, CliError> {
+    let mut command = None;
+    let mut scene = None;
+    let mut overrides = Overrides::default();
+
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| {
+            args.next().ok_or_else(|| CliError::Usage(format!("missing value after {}", name)))
+        };
+
+        match arg.as_str() {
+            "-h" | "--help" => return Err(CliError::Help),
+            "-o" | "--output" => overrides.output = Some(value(&arg)?),
+            "-r" | "--resolution" => overrides.resolution = Some(resolution(&value(&arg)?)?),
+            "-t" | "--threads" => overrides.threads = Some(positive(&arg, &value(&arg)?)?),
+            "-d" | "--depth" => overrides.depth = Some(number(&arg, &value(&arg)?)?),
+            "-s" | "--samples" => overrides.samples = Some(positive(&arg, &value(&arg)?)?),
+            "-f" | "--flags" => overrides.flags = Some(flags(&value(&arg)?)?),
+            _ if arg.starts_with('-') => return Err(CliError::Usage(format!("unknown option {}", arg))),
+            "render" if command.is_none() && scene.is_none() => command = Some(Command::Render),
+            "validate" if command.is_none() && scene.is_none() => command = Some(Command::Validate),
+            "info" if command.is_none() && scene.is_none() => command = Some(Command::Info),
+            _ if scene.is_none() => scene = Some(arg),
+            _ => return Err(CliError::Usage(format!("unexpected argument {}", arg))),
+        }
+    }
+
+    let scene = scene.ok_or_else(|| CliError::Usage("missing scene file".to_owned()))?;
+    Ok(Cli { command: command.unwrap_or(Command::Render), scene, overrides })
+}
+
+fn number(name: &str, value: &str) -> Result<usize, CliError> {
+    value.parse().map_err(|_| CliError::Usage(format!("{} expects a number, got `{}`", name, value)))
+}
+
+fn positive(name: &str, value: &str) -> Result<usize, CliError> {
+    match number(name, value)? {
+        0 => Err(CliError::Usage(format!("{} must be at least 1", name))),
+        value => Ok(value),
+    }
+}
+
+fn resolution(value: &str) -> Result<(usize, usize), CliError> {
+    let error = || CliError::Usage(format!("resolution expects <width>x<height>, got `{}`", value));
+    let (x, y) = value.split_once(['x', 'X']).ok_or_else(error)?;
+
+    match (x.parse(), y.parse()) {
+        (Ok(x), Ok(y)) if x > 0 && y > 0 => Ok((x, y)),
+        _ => Err(error()),
+    }
+}
+
+fn flags(value: &str) -> Result<u8, CliError> {
+    value.split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .try_fold(0, |flags, name| match Camera::flag(name) {
+            Some(flag) => Ok(flags | flag),
+            None => Err(CliError::Usage(format!("unknown flag `{}`", name))),
+        })
+}
+
+impl Overrides {
+    /// Replace the settings of the scene file by the ones of the command line
+    pub fn apply(&self, camera: &mut Camera, config: &mut Config) {
+        if let Some(output) = &self.output {
+            config.output = output.clone();
+        }
+
+        if let Some((x, y)) = self.resolution {
+            camera.set_size(x, y);
+        }
+
+        if let Some(threads) = self.threads {
+            config.threads = threads;
+        }
+
+        if let Some(depth) = self.depth {
+            config.depth = depth;
+        }
+
+        if let Some(samples) = self.samples {
+            config.samples = samples;
+        }
+
+        if let Some(flags) = self.flags {
+            camera.set_flags(flags);
+        }
+    }
+}
+
+/// Print a summary of the scene and of its render settings
+pub fn print_info(scene: &Scene, camera: &Camera, config: &Config) {
+    let (x, y) = camera.size();
+    let flags: Vec<&str> = Camera::FLAGS.iter()
+        .filter(|(_, flag)| camera.flags() & flag != 0)
+        .map(|(name, _)| *name)
+        .collect();
+
+    println!("objects     : {}", scene.objects().len());
+    println!("lights      : {}", scene.lights().len());
+
+    if let Some(bounds) = scene.bounds() {
+        let (min, max) = (bounds.min, bounds.max);
+        println!("bounds      : ({}, {}, {}) to ({}, {}, {})", min.x, min.y, min.z, max.x, max.y, max.z);
+    }
+
+    println!("resolution  : {}x{}", x, y);
+
+    match camera.focal() {
+        Focal::Perspective(size) => println!("focal       : perspective {}", size),
+        Focal::Orthographic(size) => println!("focal       : orthographic {}", size),
+    }
+
+    if let Some(lens) = camera.lens() {
+        println!("lens        : aperture {}, focus {}", lens.aperture, lens.focus);
+    }
+
+    match flags.is_empty() {
+        true => println!("flags       : none"),
+        false => println!("flags       : {}", flags.join(", ")),
+    }
+    println!("output      : {}", config.output);
+    println!("threads     : {}", config.threads);
+    println!("integrator  : {:?}, depth {}", config.integrator, config.depth);
+    println!("samples     : {} {:?}, {:?} filter", config.samples, config.pattern, config.filter.kind());
+    println!("acceleration: {:?}", config.acceleration);
+
+    if !config.passes.is_empty() {
+        let passes: Vec<&str> = config.passes.iter().map(|pass| pass.name()).collect();
+        println!("passes      : {}", passes.join(", "));
+    }
+}
@@ -0,0 +1,25 @@
+pub mod material;
+pub mod parser;
+pub mod object;
+pub mod scene;
+pub mod math;
+pub mod bvh;
+pub mod output;
+pub mod sampler;
+pub mod tile;
+pub mod stats;
+
+pub use scene::Scene;
+pub use object::{Object, Movable, camera::Camera, light::Light};
+pub use material::{MatProvider, Material, Color};
+pub use parser::{Config, parse_file};
+pub use output::Framebuffer;
+
+/// ### Brief
+/// Render **scene** seen by **camera** in memory, nothing is written to the disk
+///
+/// ### Params
+/// **config** Threads, depth, integrator, sampling and passes to use, the output file is ignored
+pub fn render(scene: &Scene, camera: &Camera, config: &Config) -> Framebuffer {
+    camera.render(scene, config)
+}
@@ -1,18 +1,38 @@
-mod material;
-mod parser;
-mod object;
-mod scene;
-mod math;
+mod cli;
+
+use cli::{Command, CliError};
 
 fn main() {
-    let path = match std::env::args().nth(1) {
-        Some(path) => path,
-        None => {
-            println!("usage: rustracer.exe <scene.json>");
+    let cli = match cli::parse(std::env::args().skip(1)) {
+        Ok(cli) => cli,
+        Err(CliError::Help) => {
+            println!("{}", cli::USAGE);
             return;
         }
+        Err(CliError::Usage(message)) => {
+            eprintln!("error: {}\n\n{}", message, cli::USAGE);
+            std::process::exit(2);
+        }
     };
 
-    let (scene, camera, config) = parser::parse_file(path.as_str());
-    camera.render_in(&scene, config.output.as_str(), config.depth, config.threads);
+    let (scene, mut camera, mut config) = match rustracer::parse_file(cli.scene.as_str()) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    cli.overrides.apply(&mut camera, &mut config);
+
+    match cli.command {
+        Command::Validate => println!("{}: ok", cli.scene),
+        Command::Info => cli::print_info(&scene, &camera, &config),
+        Command::Render => {
+            if let Err(e) = camera.render_in(&scene, &config) {
+                eprintln!("error: {}: {}", config.output, e);
+                std::process::exit(1);
+            }
+        }
+    }
 }
@@ -5,9 +5,9 @@ pub mod grid_mat;
 pub mod texture;
 
 use serde::{Deserialize, Deserializer, de::{Visitor, Error, Unexpected, SeqAccess, MapAccess, value::MapAccessDeserializer}};
-use std::ops::{Mul, Add, AddAssign, Sub};
+use std::ops::{Mul, Div, Add, AddAssign, Sub};
 
-pub trait MatProvider {
+pub trait MatProvider: Send + Sync {
     fn material(&self, x: f32, y: f32) -> Material;
 }
 
@@ -67,27 +67,62 @@ impl<'de> Deserialize<'de> for Box<dyn MatProvider> {
     }
 }
 
-#[derive(Default, Clone, Copy, PartialEq, Eq, Debug)]
+/// Linear RGB radiance, 1.0 stands for the 255 of the 8 bits colors
+/// but nothing prevents a channel from going above
+#[derive(Default, Clone, Copy, PartialEq, Debug)]
 pub struct Color {
-    pub red: u8,
-    pub green: u8,
-    pub blue: u8,
+    pub red: f32,
+    pub green: f32,
+    pub blue: f32,
 }
 
 impl Color {
-    pub const SKY: Color = Color { red: 50, green: 120, blue: 170};
+    pub const SKY: Color = Color { red: 50.0 / 255.0, green: 120.0 / 255.0, blue: 170.0 / 255.0 };
+    pub const WHITE: Color = Color { red: 1.0, green: 1.0, blue: 1.0 };
+    pub const BLACK: Color = Color { red: 0.0, green: 0.0, blue: 0.0 };
 
-    pub fn new(red: u8, green: u8, blue: u8) -> Self {
+    pub fn new(red: f32, green: f32, blue: f32) -> Self {
         Self { red, green, blue }
     }
 
-    pub fn new_gray(gray: u8) -> Self {
+    pub fn new_gray(gray: f32) -> Self {
         Self { red: gray, green: gray, blue: gray }
     }
 
-    pub fn to_array(self) -> [u8; 3] {
+    pub fn from_rgb8(red: u8, green: u8, blue: u8) -> Self {
+        Self::new(red as f32 / 255.0, green as f32 / 255.0, blue as f32 / 255.0)
+    }
+
+    pub fn from_gray8(gray: u8) -> Self {
+        Self::new_gray(gray as f32 / 255.0)
+    }
+
+    /// Quantize to 8 bits, channels out of [0, 1] are clipped
+    pub fn to_rgb8(self) -> [u8; 3] {
+        self.to_array().map(|channel| (channel.clamp(0.0, 1.0) * 255.0).round() as u8)
+    }
+
+    pub fn to_array(self) -> [f32; 3] {
         [self.red, self.green, self.blue]
     }
+
+    /// Every channels brought back in [0, 1]
+    pub fn clamped(self) -> Self {
+        Self {
+            red: self.red.clamp(0.0, 1.0),
+            green: self.green.clamp(0.0, 1.0),
+            blue: self.blue.clamp(0.0, 1.0),
+        }
+    }
+
+    /// Perceived brightness, with the Rec. 709 weights
+    pub fn luminance(&self) -> f32 {
+        0.2126 * self.red + 0.7152 * self.green + 0.0722 * self.blue
+    }
+
+    pub fn max_channel(&self) -> f32 {
+        self.red.max(self.green).max(self.blue)
+    }
 }
 
 impl Mul for Color {
@@ -95,9 +130,9 @@ impl Mul for Color {
 
     fn mul(self, rhs: Self) -> Self::Output {
         Self {
-            red: ((self.red as f32 / 255.0) * (rhs.red as f32 / 255.0) * 255.0).clamp(0.0, 255.0) as u8,
-            green: ((self.green as f32 / 255.0) * (rhs.green as f32 / 255.0) * 255.0).clamp(0.0, 255.0) as u8,
-            blue: ((self.blue as f32 / 255.0) * (rhs.blue as f32 / 255.0) * 255.0).clamp(0.0, 255.0) as u8,
+            red: self.red * rhs.red,
+            green: self.green * rhs.green,
+            blue: self.blue * rhs.blue,
         }
     }
 }
@@ -107,9 +142,21 @@ impl Mul<f32> for Color {
 
     fn mul(self, coef: f32) -> Self::Output {
         Self {
-            red: (self.red as f32 * coef).clamp(0.0, 255.0) as u8,
-            green: (self.green as f32 * coef).clamp(0.0, 255.0) as u8,
-            blue: (self.blue as f32 * coef).clamp(0.0, 255.0) as u8,
+            red: self.red * coef,
+            green: self.green * coef,
+            blue: self.blue * coef,
+        }
+    }
+}
+
+impl Div<f32> for Color {
+    type Output = Self;
+
+    fn div(self, coef: f32) -> Self::Output {
+        Self {
+            red: self.red / coef,
+            green: self.green / coef,
+            blue: self.blue / coef,
         }
     }
 }
@@ -119,29 +166,30 @@ impl Add for Color {
 
     fn add(self, rhs: Self) -> Self::Output {
         Self {
-            red: self.red.saturating_add(rhs.red),
-            green: self.green.saturating_add(rhs.green),
-            blue: self.blue.saturating_add(rhs.blue),
+            red: self.red + rhs.red,
+            green: self.green + rhs.green,
+            blue: self.blue + rhs.blue,
         }
     }
 }
 
 impl AddAssign for Color {
     fn add_assign(&mut self, rhs: Self) {
-        self.red = self.red.saturating_add(rhs.red);
-        self.green = self.green.saturating_add(rhs.green);
-        self.blue = self.blue.saturating_add(rhs.blue);
+        self.red += rhs.red;
+        self.green += rhs.green;
+        self.blue += rhs.blue;
     }
 }
 
 impl Sub for Color {
     type Output = Self;
 
+    /// Channels can't go below zero, negative light doesn't exist
     fn sub(self, rhs: Self) -> Self::Output {
         Self {
-            red: self.red.saturating_sub(rhs.red),
-            green: self.green.saturating_sub(rhs.green),
-            blue: self.blue.saturating_sub(rhs.blue),
+            red: (self.red - rhs.red).max(0.0),
+            green: (self.green - rhs.green).max(0.0),
+            blue: (self.blue - rhs.blue).max(0.0),
         }
     }
 }
@@ -154,23 +202,31 @@ impl<'de> Deserialize<'de> for Color {
             type Value = Color;
 
             fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
-                formatter.write_str("u8 or array of size 3")
+                formatter.write_str("number or array of size 3, on a 0 to 255 scale")
             }
 
             fn visit_u64<E: Error>(self, gray: u64) -> Result<Self::Value, E> {
-                if gray <= u64::from(u8::MAX) {
-                    Ok(Color::new_gray(gray as u8))
+                Ok(Color::new_gray(gray as f32 / 255.0))
+            }
+
+            fn visit_f64<E: Error>(self, gray: f64) -> Result<Self::Value, E> {
+                if gray >= 0.0 {
+                    Ok(Color::new_gray(gray as f32 / 255.0))
                 } else {
-                    Err(Error::invalid_type(Unexpected::Unsigned(gray), &self))
+                    Err(Error::invalid_value(Unexpected::Float(gray), &self))
                 }
             }
 
             fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error> where A: SeqAccess<'de> {
-                let red = seq.next_element()?.ok_or_else(|| Error::invalid_length(0, &self))?;
-                let green = seq.next_element()?.ok_or_else(|| Error::invalid_length(1, &self))?;
-                let blue = seq.next_element()?.ok_or_else(|| Error::invalid_length(2, &self))?;
+                let red: f32 = seq.next_element()?.ok_or_else(|| Error::invalid_length(0, &self))?;
+                let green: f32 = seq.next_element()?.ok_or_else(|| Error::invalid_length(1, &self))?;
+                let blue: f32 = seq.next_element()?.ok_or_else(|| Error::invalid_length(2, &self))?;
+
+                if red < 0.0 || green < 0.0 || blue < 0.0 {
+                    return Err(Error::custom("color channels can't be negative"));
+                }
 
-                Ok(Color::new(red, green, blue))
+                Ok(Color::new(red / 255.0, green / 255.0, blue / 255.0))
             }
         }
 
@@ -198,9 +254,9 @@ impl Material {
 impl Default for Material {
     fn default() -> Self {
         Self {
-            ambient: Color::new_gray(63),
-            diffuse: Color::new_gray(127),
-            specular: Color::new_gray(191),
+            ambient: Color::from_gray8(63),
+            diffuse: Color::from_gray8(127),
+            specular: Color::from_gray8(191),
             alpha: 255,
             reflection: 0,
             shininess: 50.0,
@@ -1,7 +1,7 @@
 use crate::material::{MatProvider, Material, Color};
 
-use serde::{Deserialize, Deserializer, de::{Visitor, Error, MapAccess}};
-use image::{DynamicImage, GenericImageView, io::Reader};
+use serde::{Deserialize, Deserializer, de::{Visitor, Error, MapAccess, Unexpected}};
+use image::{DynamicImage, GenericImageView, ImageResult, io::Reader};
 
 pub struct Texture {
     image: DynamicImage,
@@ -12,15 +12,14 @@ pub struct Texture {
 }
 
 impl Texture {
-    pub fn new(file_name: &str, rep_x: usize, rep_y: usize, reflection: u8, shininess: f32) -> Self {
+    /// ### Brief
+    /// Load the image **file_name**, repeated **rep_x** and **rep_y** times
+    /// over the texture coordinates
+    pub fn new(file_name: &str, rep_x: usize, rep_y: usize, reflection: u8, shininess: f32) -> ImageResult<Self> {
         assert!(rep_x > 0 && rep_y > 0);
 
-        let image = match Reader::open(file_name) {
-            Ok(image) => image.decode().unwrap(),
-            Err(e) => panic!("{}: {}", file_name, e),
-        };
-
-        Self { image, rep_x: rep_x as f32, rep_y: rep_y as f32, reflection, shininess }
+        let image = Reader::open(file_name)?.decode()?;
+        Ok(Self { image, rep_x: rep_x as f32, rep_y: rep_y as f32, reflection, shininess })
     }
 }
 
@@ -31,12 +30,12 @@ impl MatProvider for Texture {
         let x = (x * self.rep_x % 1.0).min(1.0 - f32::EPSILON) * w as f32;
         let y = (y * self.rep_y % 1.0).min(1.0 - f32::EPSILON) * h as f32;
         let pix = self.image.get_pixel(x as u32, y as u32).0;
-        let color = Color::new(pix[0], pix[1], pix[2]);
+        let color = Color::from_rgb8(pix[0], pix[1], pix[2]);
 
         Material::new(
             color * 0.5,
             color,
-            color * 1.5,
+            (color * 1.5).clamped(),
             pix[3],
             self.reflection,
             self.shininess
@@ -85,7 +84,12 @@ impl<'de> Deserialize<'de> for Texture {
                 let rep_x = rep_x.unwrap_or(1);
                 let rep_y = rep_y.unwrap_or(1);
 
-                Ok(Texture::new(file_name, rep_x, rep_y, reflection, shininess))
+                if rep_x == 0 || rep_y == 0 {
+                    return Err(Error::invalid_value(Unexpected::Unsigned(0), &"at least one repetition"));
+                }
+
+                Texture::new(file_name, rep_x, rep_y, reflection, shininess)
+                    .map_err(|e| Error::custom(format!("{}: {}", file_name, e)))
             }
         }
 
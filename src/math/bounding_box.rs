@@ -0,0 +1,102 @@
+use crate::math::{
+    point::Point,
+    ray::Ray,
+};
+
+use rulinalg::matrix::Matrix;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoundingBox {
+    pub min: Point,
+    pub max: Point,
+}
+
+impl BoundingBox {
+    pub fn new(min: Point, max: Point) -> Self {
+        Self { min, max }
+    }
+
+    /// Box holding nothing, neutral element of **union**
+    pub fn empty() -> Self {
+        Self {
+            min: Point::new(f32::INFINITY, f32::INFINITY, f32::INFINITY),
+            max: Point::new(f32::NEG_INFINITY, f32::NEG_INFINITY, f32::NEG_INFINITY),
+        }
+    }
+
+    pub fn from_points<'a, I>(points: I) -> Self where I: IntoIterator<Item = &'a Point> {
+        points.into_iter().fold(Self::empty(), |bbox, point| bbox.grow(point))
+    }
+
+    pub fn grow(self, point: &Point) -> Self {
+        Self {
+            min: Point::new(self.min.x.min(point.x), self.min.y.min(point.y), self.min.z.min(point.z)),
+            max: Point::new(self.max.x.max(point.x), self.max.y.max(point.y), self.max.z.max(point.z)),
+        }
+    }
+
+    pub fn union(self, other: &BoundingBox) -> Self {
+        self.grow(&other.min).grow(&other.max)
+    }
+
+    pub fn centroid(&self) -> Point {
+        (self.min + self.max) * 0.5
+    }
+
+    pub fn extent(&self) -> Point {
+        self.max - self.min
+    }
+
+    pub fn corners(&self) -> [Point; 8] {
+        let (min, max) = (&self.min, &self.max);
+
+        [
+            Point::new(min.x, min.y, min.z),
+            Point::new(max.x, min.y, min.z),
+            Point::new(min.x, max.y, min.z),
+            Point::new(max.x, max.y, min.z),
+            Point::new(min.x, min.y, max.z),
+            Point::new(max.x, min.y, max.z),
+            Point::new(min.x, max.y, max.z),
+            Point::new(max.x, max.y, max.z),
+        ]
+    }
+
+    /// ### Brief
+    /// Box enclosing this one once moved by the matrix **tra**
+    pub fn transform(&self, tra: &Matrix<f32>) -> Self {
+        let corners = self.corners().map(|corner| (tra * corner.into_pt4()).into_pt());
+        Self::from_points(corners.iter())
+    }
+
+    /// ### Brief
+    /// Slab test of **ray** against the box
+    ///
+    /// ### Return
+    /// The ray coefficients where the ray enter and leave the box
+    pub fn intersect(&self, ray: &Ray) -> Option<(f32, f32)> {
+        let (origin, vector) = (ray.origin(), ray.vector());
+        let mut near = f32::NEG_INFINITY;
+        let mut far = f32::INFINITY;
+
+        for (o, v, min, max) in [
+            (origin.x, vector.x, self.min.x, self.max.x),
+            (origin.y, vector.y, self.min.y, self.max.y),
+            (origin.z, vector.z, self.min.z, self.max.z),
+        ] {
+            let inv = 1.0 / v;
+            let t1 = (min - o) * inv;
+            let t2 = (max - o) * inv;
+
+            // f32::min and f32::max ignore the NaN of rays parallel to a slab
+            near = near.max(t1.min(t2));
+            far = far.min(t1.max(t2));
+        }
+
+        if near <= far && far >= 0.0 {
+            Some((near, far))
+        } else {
+            None
+        }
+    }
+}
@@ -1,3 +1,7 @@
+pub mod bounding_box;
 pub mod h_coord;
 pub mod point;
 pub mod ray;
+pub mod roots;
+pub mod sampling;
+pub mod transform;
@@ -33,6 +33,14 @@ impl Point {
     pub fn dot(&self, rhs: &Point) -> f32 {
         self.x * rhs.x + self.y * rhs.y + self.z * rhs.z
     }
+
+    pub fn cross(&self, rhs: &Point) -> Point {
+        Point {
+            x: self.y * rhs.z - self.z * rhs.y,
+            y: self.z * rhs.x - self.x * rhs.z,
+            z: self.x * rhs.y - self.y * rhs.x,
+        }
+    }
 }
 
 use std::ops::{Add, Sub, Mul, Div, Neg};
@@ -0,0 +1,211 @@
+use std::f64::consts::TAU;
+
+/// ### Brief
+/// Real roots of `a x² + b x + c`, the smallest first
+///
+/// ### Return
+/// `None` when there is none or when the polynomial is degenerated, `a` being zero
+pub fn quadratic(a: f32, b: f32, c: f32) -> Option<(f32, f32)> {
+    if a == 0.0 {
+        return None;
+    }
+
+    let d = b * b - 4.0 * a * c;
+    if d < 0.0 {
+        return None;
+    }
+
+    // avoid subtracting two close numbers, which loses the precision of the small root
+    let q = -0.5 * (b + b.signum() * d.sqrt());
+    let (x1, x2) = match q == 0.0 {
+        true => (0.0, 0.0),
+        false => (q / a, c / q),
+    };
+
+    Some((x1.min(x2), x1.max(x2)))
+}
+
+/// ### Brief
+/// Real roots of `a x⁴ + b x³ + c x² + d x + e` by Ferrari's method,
+/// computed in double precision and refined by Newton's method
+///
+/// ### Return
+/// The roots, the smallest first, and how many of them there are
+pub fn quartic(a: f64, b: f64, c: f64, d: f64, e: f64) -> ([f64; 4], usize) {
+    let mut roots = [0.0; 4];
+    let mut count = 0;
+
+    if a == 0.0 {
+        return (roots, count);
+    }
+
+    let (b, c, d, e) = (b / a, c / a, d / a, e / a);
+
+    // x = y - b / 4 gives the depressed quartic y⁴ + p y² + q y + r
+    let shift = -b / 4.0;
+    let p = c - 3.0 * b * b / 8.0;
+    let q = d - b * c / 2.0 + b * b * b / 8.0;
+    let r = e - b * d / 4.0 + b * b * c / 16.0 - 3.0 * b * b * b * b / 256.0;
+
+    let mut push = |y: f64| {
+        roots[count] = y + shift;
+        count += 1;
+    };
+
+    if q.abs() < 1e-12 {
+        // biquadratic, z = y²
+        if let Some((z1, z2)) = quadratic_f64(1.0, p, r) {
+            for z in [z1, z2] {
+                if z >= 0.0 {
+                    push(-z.sqrt());
+                    push(z.sqrt());
+                }
+            }
+        }
+    } else {
+        // y⁴ + p y² + q y + r = (y² + p / 2 + m)² - (2m y² - q y + m² + p m + p² / 4 - r)
+        // where the second term is a perfect square for m root of the resolvent cubic,
+        // which has a positive root as it is -q² at 0
+        let m = cubic_max_root(p, p * p / 4.0 - r, -q * q / 8.0);
+        let m = m.max(f64::EPSILON);
+        let s = (2.0 * m).sqrt();
+
+        for (sign, offset) in [(-1.0, q / (2.0 * s)), (1.0, -q / (2.0 * s))] {
+            if let Some((y1, y2)) = quadratic_f64(1.0, sign * s, p / 2.0 + m + offset) {
+                push(y1);
+                push(y2);
+            }
+        }
+    }
+
+    let value = |x: f64| (((x + b) * x + c) * x + d) * x + e;
+    let slope = |x: f64| ((4.0 * x + 3.0 * b) * x + 2.0 * c) * x + d;
+
+    for root in roots[..count].iter_mut() {
+        for _ in 0..2 {
+            // close to a double root the slope vanishes, only keep the steps that help
+            let next = *root - value(*root) / slope(*root);
+            if next.is_finite() && value(next).abs() < value(*root).abs() {
+                *root = next;
+            }
+        }
+    }
+
+    roots[..count].sort_unstable_by(|x, y| x.total_cmp(y));
+    (roots, count)
+}
+
+/// Same as `quadratic` in double precision, double roots pushed
+/// slightly below zero by rounding errors are kept
+fn quadratic_f64(a: f64, b: f64, c: f64) -> Option<(f64, f64)> {
+    let d = b * b - 4.0 * a * c;
+    if d < -1e-9 * (b * b).max((4.0 * a * c).abs()) {
+        return None;
+    }
+
+    let d = d.max(0.0);
+
+    let q = -0.5 * (b + b.signum() * d.sqrt());
+    let (x1, x2) = match q == 0.0 {
+        true => (0.0, 0.0),
+        false => (q / a, c / q),
+    };
+
+    Some((x1.min(x2), x1.max(x2)))
+}
+
+/// ### Brief
+/// Largest real root of the monic cubic `x³ + a x² + b x + c`
+fn cubic_max_root(a: f64, b: f64, c: f64) -> f64 {
+    let q = (a * a - 3.0 * b) / 9.0;
+    let r = (2.0 * a * a * a - 9.0 * a * b + 27.0 * c) / 54.0;
+
+    let root = if r * r < q * q * q {
+        // three real roots, -2 √q cos((theta + 2kπ) / 3) - a / 3, the largest for k = 1
+        let theta = (r / (q * q * q).sqrt()).clamp(-1.0, 1.0).acos();
+        -2.0 * q.sqrt() * ((theta + TAU) / 3.0).cos() - a / 3.0
+    } else {
+        let big = -r.signum() * (r.abs() + (r * r - q * q * q).sqrt()).cbrt();
+        let small = if big != 0.0 { q / big } else { 0.0 };
+        big + small - a / 3.0
+    };
+
+    // the closed forms lose precision when roots are close, one Newton step fixes it
+    let value = ((root + a) * root + b) * root + c;
+    let slope = (3.0 * root + 2.0 * a) * root + b;
+
+    match slope != 0.0 {
+        true => root - value / slope,
+        false => root,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Roots found for `a x⁴ + b x³ + c x² + d x + e`, checking each of them solves it
+    fn solve(a: f64, b: f64, c: f64, d: f64, e: f64) -> Vec<f64> {
+        let (roots, count) = quartic(a, b, c, d, e);
+        let roots = roots[..count].to_vec();
+
+        for x in roots.iter() {
+            let value = (((a * x + b) * x + c) * x + d) * x + e;
+            assert!(value.abs() < 1e-6, "{} isn't a root, the polynomial gives {}", x, value);
+        }
+        roots
+    }
+
+    fn assert_close(roots: &[f64], expected: &[f64], tolerance: f64) {
+        assert_eq!(roots.len(), expected.len(), "found {:?} instead of {:?}", roots, expected);
+        for (root, expected) in roots.iter().zip(expected) {
+            assert!((root - expected).abs() < tolerance, "found {:?} instead of {:?}", roots, expected);
+        }
+    }
+
+    #[test]
+    fn four_real_roots() {
+        // (x - 1)(x - 2)(x - 3)(x - 4)
+        let roots = solve(1.0, -10.0, 35.0, -50.0, 24.0);
+        assert_close(&roots, &[1.0, 2.0, 3.0, 4.0], 1e-9);
+
+        // (x + 3)(x + 0.5)(x - 0.25)(x - 7), scaled by 2
+        let roots = solve(2.0, -7.5, -44.25, -9.5, 5.25);
+        assert_close(&roots, &[-3.0, -0.5, 0.25, 7.0], 1e-9);
+    }
+
+    #[test]
+    fn double_roots() {
+        // (x - 1)² (x - 2)², symmetric around its shift so it is biquadratic
+        let roots = solve(1.0, -6.0, 13.0, -12.0, 4.0);
+        assert_close(&roots, &[1.0, 1.0, 2.0, 2.0], 1e-6);
+
+        // (x - 1)² (x + 2)(x - 3), like a ray grazing a torus
+        let roots = solve(1.0, -3.0, -3.0, 11.0, -6.0);
+        assert_close(&roots, &[-2.0, 1.0, 1.0, 3.0], 1e-6);
+    }
+
+    #[test]
+    fn no_real_roots() {
+        // x⁴ + 1
+        assert!(solve(1.0, 0.0, 0.0, 0.0, 1.0).is_empty());
+
+        // (x² + 1)(x² + 2x + 5)
+        assert!(solve(1.0, 2.0, 6.0, 2.0, 5.0).is_empty());
+    }
+
+    #[test]
+    fn near_degenerate_leading_coefficient() {
+        // barely a quartic, the roots of x² - 3x + 2 are only nudged by the
+        // quartic term while the two others, huge, are complex
+        let roots = solve(1e-6, 0.0, 1.0, -3.0, 2.0);
+        assert_close(&roots, &[1.0, 2.0], 1e-4);
+
+        // with the opposite sign the two others are real, far on each side
+        let roots = solve(1e-6, 0.0, -1.0, 3.0, -2.0);
+        assert_close(&roots, &[-1001.5, 1.0, 2.0, 998.5], 1e-2);
+
+        // without the quartic term there is nothing to solve
+        assert_eq!(quartic(0.0, 0.0, 1.0, -3.0, 2.0).1, 0);
+    }
+}
@@ -0,0 +1,75 @@
+use crate::math::point::Point;
+
+use std::f32::consts::TAU;
+
+/// ### Brief
+/// Build two unit vectors forming an orthonormal basis with **normal**
+pub fn orthonormal_basis(normal: &Point) -> (Point, Point) {
+    let helper = if normal.x.abs() > 0.9 {
+        Point::new(0.0, 1.0, 0.0)
+    } else {
+        Point::new(1.0, 0.0, 0.0)
+    };
+
+    let tangent = normal.cross(&helper).normalized();
+    let bitangent = normal.cross(&tangent);
+    (tangent, bitangent)
+}
+
+/// ### Brief
+/// Map two uniform numbers in [0, 1) to a direction of the hemisphere around
+/// **normal**, with a density proportional to the cosine with the normal
+pub fn cosine_hemisphere(normal: &Point, u1: f32, u2: f32) -> Point {
+    let (tangent, bitangent) = orthonormal_basis(normal);
+    let radius = u1.sqrt();
+    let phi = TAU * u2;
+
+    let x = radius * phi.cos();
+    let y = radius * phi.sin();
+    let z = (1.0 - u1).max(0.0).sqrt();
+
+    (tangent * x + bitangent * y + normal * z).normalized()
+}
+
+/// ### Brief
+/// Digits of **index** in base **base** mirrored around the decimal point,
+/// the dimension of a Halton sequence using this base
+pub fn radical_inverse(mut index: u32, base: u32) -> f32 {
+    let inv_base = 1.0 / base as f32;
+    let mut inv = inv_base;
+    let mut result = 0.0;
+
+    while index > 0 {
+        result += (index % base) as f32 * inv;
+        index /= base;
+        inv *= inv_base;
+    }
+
+    result.min(1.0 - f32::EPSILON)
+}
+
+/// ### Brief
+/// Point **index** of the two first dimensions of the Sobol sequence,
+/// each bit pattern is xor-ed with **scramble** to decorrelate the sequences
+pub fn sobol_2d(index: u32, scramble: (u32, u32)) -> (f32, f32) {
+    let mut x = scramble.0;
+    let mut y = scramble.1;
+    let mut direction = 1 << 31;
+    let mut bits = index;
+    let mut bit = 0;
+
+    while bits != 0 {
+        if bits & 1 != 0 {
+            x ^= 1 << (31 - bit);
+            y ^= direction;
+        }
+
+        bits >>= 1;
+        bit += 1;
+        direction ^= direction >> 1;
+    }
+
+    // keep 24 bits so the values fit in a f32 without rounding up to 1
+    let to_unit = |bits: u32| (bits >> 8) as f32 / (1 << 24) as f32;
+    (to_unit(x), to_unit(y))
+}
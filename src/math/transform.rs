@@ -0,0 +1,273 @@
+use crate::math::point::Point;
+
+use serde::{Deserialize, Deserializer, de::{self, Visitor, Error, MapAccess, SeqAccess, Unexpected}};
+use rulinalg::matrix::Matrix;
+
+/// Smallest determinant of the linear part accepted, below it the object is flattened
+const MIN_DETERMINANT: f32 = 1e-9;
+
+/// One step of an ordered `transforms` list
+#[derive(Clone, Debug, PartialEq)]
+pub enum Transform {
+    Translate(Point),
+    /// Angles in degrees, applied around Z, then Y, then X
+    Rotate(Point),
+    Scale(Point),
+    Shear(Shear),
+    /// Rows of an affine matrix, the last one being `[0, 0, 0, 1]`
+    Matrix([[f32; 4]; 4]),
+}
+
+impl Transform {
+    /// Homogeneous matrix of the step
+    pub fn matrix(&self) -> Matrix<f32> {
+        match self {
+            Transform::Translate(Point { x, y, z }) => Matrix::new(4, 4, vec![
+                1., 0., 0., *x,
+                0., 1., 0., *y,
+                0., 0., 1., *z,
+                0., 0., 0., 1.
+            ]),
+            Transform::Rotate(Point { x, y, z }) => {
+                let (sx, cx) = x.to_radians().sin_cos();
+                let (sy, cy) = y.to_radians().sin_cos();
+                let (sz, cz) = z.to_radians().sin_cos();
+
+                let rx = Matrix::new(4, 4, vec![
+                    1., 0., 0., 0.,
+                    0., cx, -sx, 0.,
+                    0., sx, cx, 0.,
+                    0., 0., 0., 1.
+                ]);
+                let ry = Matrix::new(4, 4, vec![
+                    cy, 0., sy, 0.,
+                    0., 1., 0., 0.,
+                    -sy, 0., cy, 0.,
+                    0., 0., 0., 1.
+                ]);
+                let rz = Matrix::new(4, 4, vec![
+                    cz, -sz, 0., 0.,
+                    sz, cz, 0., 0.,
+                    0., 0., 1., 0.,
+                    0., 0., 0., 1.
+                ]);
+
+                rx * ry * rz
+            }
+            Transform::Scale(Point { x, y, z }) => Matrix::new(4, 4, vec![
+                *x, 0., 0., 0.,
+                0., *y, 0., 0.,
+                0., 0., *z, 0.,
+                0., 0., 0., 1.
+            ]),
+            Transform::Shear(Shear { xy, xz, yx, yz, zx, zy }) => Matrix::new(4, 4, vec![
+                1., *xy, *xz, 0.,
+                *yx, 1., *yz, 0.,
+                *zx, *zy, 1., 0.,
+                0., 0., 0., 1.
+            ]),
+            Transform::Matrix(rows) => Matrix::new(4, 4, rows.concat()),
+        }
+    }
+}
+
+/// Shear factors, **xy** being how much x moves along y
+#[derive(Clone, Copy, Default, Debug, PartialEq)]
+pub struct Shear {
+    pub xy: f32,
+    pub xz: f32,
+    pub yx: f32,
+    pub yz: f32,
+    pub zx: f32,
+    pub zy: f32,
+}
+
+/// Scale factors, given as one number for all axes or as a vector
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Scale(pub Point);
+
+/// ### Brief
+/// Every transform field of an object, light or camera
+///
+/// ### Order
+/// The local space is mapped by **matrix**, then **shear**, **scale**,
+/// **rotate** and **translate**, and finally by each step of **transforms**
+/// in the order they are listed
+#[derive(Clone, Default, Debug)]
+pub struct Placement {
+    pub translate: Option<Point>,
+    pub rotate: Option<Point>,
+    pub scale: Option<Scale>,
+    pub shear: Option<Shear>,
+    pub matrix: Option<[[f32; 4]; 4]>,
+    pub transforms: Vec<Transform>,
+}
+
+impl Placement {
+    /// ### Brief
+    /// Local to world matrix of the placement
+    ///
+    /// ### Return
+    /// An error message when the transforms can't be inverted
+    pub fn matrix(&self) -> Result<Matrix<f32>, String> {
+        let steps = self.matrix.map(Transform::Matrix).into_iter()
+            .chain(self.shear.map(Transform::Shear))
+            .chain(self.scale.map(|Scale(scale)| Transform::Scale(scale)))
+            .chain(self.rotate.map(Transform::Rotate))
+            .chain(self.translate.map(Transform::Translate))
+            .chain(self.transforms.iter().cloned());
+
+        let mat = steps.fold(Matrix::identity(4), |mat, step| step.matrix() * mat);
+
+        let linear = Matrix::new(3, 3, vec![
+            mat[[0, 0]], mat[[0, 1]], mat[[0, 2]],
+            mat[[1, 0]], mat[[1, 1]], mat[[1, 2]],
+            mat[[2, 0]], mat[[2, 1]], mat[[2, 2]],
+        ]);
+
+        match linear.det().abs() > MIN_DETERMINANT {
+            true => Ok(mat),
+            false => Err("transforms flatten the object to a plane, a line or a point".to_string()),
+        }
+    }
+
+    /// ### Brief
+    /// Read the value of **field** when it is one of the transform fields
+    ///
+    /// ### Return
+    /// False when **field** isn't a transform field, its value is left in **map**
+    pub fn visit_field<'de, A>(&mut self, field: &str, map: &mut A) -> Result<bool, A::Error> where A: MapAccess<'de> {
+        match field {
+            "transform" => self.translate = Some(map.next_value()?),
+            "rotate" => self.rotate = Some(map.next_value()?),
+            "scale" => self.scale = Some(map.next_value()?),
+            "shear" => self.shear = Some(map.next_value()?),
+            "matrix" => self.matrix = Some(map.next_value::<AffineRows>()?.0),
+            "transforms" => self.transforms = map.next_value()?,
+            _ => return Ok(false),
+        }
+
+        Ok(true)
+    }
+}
+
+impl<'de> Deserialize<'de> for Scale {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error> where D: Deserializer<'de> {
+        struct ScaleVisitor;
+
+        impl<'de> Visitor<'de> for ScaleVisitor {
+            type Value = Scale;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                formatter.write_str("a number or a Vec3 of scale factors")
+            }
+
+            fn visit_f64<E>(self, v: f64) -> Result<Self::Value, E> where E: Error {
+                let v = v as f32;
+                Ok(Scale(Point::new(v, v, v)))
+            }
+
+            fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E> where E: Error {
+                self.visit_f64(v as f64)
+            }
+
+            fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E> where E: Error {
+                self.visit_f64(v as f64)
+            }
+
+            fn visit_seq<A>(self, seq: A) -> Result<Self::Value, A::Error> where A: SeqAccess<'de> {
+                Point::deserialize(de::value::SeqAccessDeserializer::new(seq)).map(Scale)
+            }
+
+            fn visit_map<A>(self, map: A) -> Result<Self::Value, A::Error> where A: MapAccess<'de> {
+                Point::deserialize(de::value::MapAccessDeserializer::new(map)).map(Scale)
+            }
+        }
+
+        deserializer.deserialize_any(ScaleVisitor)
+    }
+}
+
+impl<'de> Deserialize<'de> for Shear {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error> where D: Deserializer<'de> {
+        const FIELDS: &[&str] = &["xy", "xz", "yx", "yz", "zx", "zy"];
+        struct ShearVisitor;
+
+        impl<'de> Visitor<'de> for ShearVisitor {
+            type Value = Shear;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                formatter.write_str("Shear struct")
+            }
+
+            fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error> where A: MapAccess<'de> {
+                let mut shear = Shear::default();
+
+                while let Some(field) = map.next_key()? {
+                    match field {
+                        "xy" => shear.xy = map.next_value()?,
+                        "xz" => shear.xz = map.next_value()?,
+                        "yx" => shear.yx = map.next_value()?,
+                        "yz" => shear.yz = map.next_value()?,
+                        "zx" => shear.zx = map.next_value()?,
+                        "zy" => shear.zy = map.next_value()?,
+                        _ => return Err(Error::unknown_field(field, FIELDS)),
+                    }
+                }
+
+                Ok(shear)
+            }
+        }
+
+        deserializer.deserialize_map(ShearVisitor)
+    }
+}
+
+impl<'de> Deserialize<'de> for Transform {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error> where D: Deserializer<'de> {
+        const FIELDS: &[&str] = &["translate", "rotate", "scale", "shear", "matrix"];
+        struct TransformVisitor;
+
+        impl<'de> Visitor<'de> for TransformVisitor {
+            type Value = Transform;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                formatter.write_str("a map with a single translate, rotate, scale, shear or matrix field")
+            }
+
+            fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error> where A: MapAccess<'de> {
+                let field: &str = map.next_key()?.ok_or_else(|| Error::invalid_length(0, &self))?;
+
+                let transform = match field {
+                    "translate" => Transform::Translate(map.next_value()?),
+                    "rotate" => Transform::Rotate(map.next_value()?),
+                    "scale" => Transform::Scale(map.next_value::<Scale>()?.0),
+                    "shear" => Transform::Shear(map.next_value()?),
+                    "matrix" => Transform::Matrix(map.next_value::<AffineRows>()?.0),
+                    _ => return Err(Error::unknown_field(field, FIELDS)),
+                };
+
+                match map.next_key::<&str>()? {
+                    None => Ok(transform),
+                    Some(_) => Err(Error::invalid_length(2, &self)),
+                }
+            }
+        }
+
+        deserializer.deserialize_map(TransformVisitor)
+    }
+}
+
+/// Rows of an affine matrix, whose last one must be `[0, 0, 0, 1]`
+struct AffineRows([[f32; 4]; 4]);
+
+impl<'de> Deserialize<'de> for AffineRows {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error> where D: Deserializer<'de> {
+        let rows = <[[f32; 4]; 4]>::deserialize(deserializer)?;
+
+        match rows[3] == [0.0, 0.0, 0.0, 1.0] {
+            true => Ok(AffineRows(rows)),
+            false => Err(Error::invalid_value(Unexpected::Seq, &"an affine matrix, whose last row is [0, 0, 0, 1]")),
+        }
+    }
+}
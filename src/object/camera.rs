@@ -1,22 +1,45 @@
-use crate::object::{Movable, Object};
+use crate::object::{Movable, hit::Hit, GAP};
+use crate::parser::{Config, Integrator};
+use crate::material::{Color, Material};
+use crate::math::sampling::cosine_hemisphere;
 use crate::math::point::Point;
-use crate::material::Color;
+use crate::math::transform::Placement;
 use crate::math::ray::Ray;
 use crate::scene::Scene;
+use crate::output::{Framebuffer, Sample};
+use crate::stats::{self, Stats, Progress};
 
-use serde::{Deserialize, Deserializer, de::{Visitor, Error, MapAccess}};
+use serde::{Deserialize, Deserializer, de::{Visitor, Error, MapAccess, Unexpected}};
+use rand::{Rng, SeedableRng, rngs::SmallRng};
 use rulinalg::matrix::Matrix;
+use std::f32::consts::TAU;
+use std::sync::{mpsc, atomic::{AtomicUsize, Ordering}};
+use std::io::Write;
+use std::time::Duration;
+
+/// Bounce from which paths may be terminated by russian roulette
+const RUSSIAN_ROULETTE_BOUNCE: usize = 3;
 
 pub enum Focal {
     Perspective(f32),
     Orthographic(f32),
 }
 
+/// Thin lens of a camera, only the objects at the focus distance are sharp
+#[derive(Clone, Copy, Debug)]
+pub struct Lens {
+    /// Radius of the lens aperture
+    pub aperture: f32,
+    /// Distance of the sharp plane from the camera
+    pub focus: f32,
+}
+
 pub struct Camera {
     tra: Matrix<f32>,
     inv: Matrix<f32>,
 
     focal: Focal,
+    lens: Option<Lens>,
     flags: u8,
     x: usize,
     y: usize,
@@ -30,97 +53,264 @@ impl Camera {
         Camera {
             tra: Matrix::identity(4),
             inv: Matrix::identity(4),
+            lens: None,
             flags: 0, x, y, focal,
         }
     }
 
+    /// Names of the flags in the scene files, along their value
+    pub const FLAGS: &'static [(&'static str, u8)] = &[
+        ("ANTI_ALIASING", Camera::ANTI_ALIASING),
+        ("NO_SHADOW", Camera::NO_SHADOW),
+    ];
+
+    /// Value of the flag named **name** in the scene files
+    pub fn flag(name: &str) -> Option<u8> {
+        Camera::FLAGS.iter().find(|(flag, _)| *flag == name).map(|(_, value)| *value)
+    }
+
+    pub fn flags(&self) -> u8 {
+        self.flags
+    }
+
     pub fn set_flags(&mut self, flags: u8) {
         self.flags = flags;
     }
 
+    /// Width and height of the rendered image, in pixels
+    pub fn size(&self) -> (usize, usize) {
+        (self.x, self.y)
+    }
+
+    pub fn set_size(&mut self, x: usize, y: usize) {
+        self.x = x;
+        self.y = y;
+    }
+
+    pub fn focal(&self) -> &Focal {
+        &self.focal
+    }
+
+    pub fn lens(&self) -> Option<Lens> {
+        self.lens
+    }
+
+    /// Blur the objects out of the focus distance of **lens**, `None` keeps everything sharp
+    pub fn set_lens(&mut self, lens: Option<Lens>) {
+        self.lens = lens;
+    }
+
     /// ### Brief
-    /// Allow to render the Scene **scene** in a file named **file_name**
+    /// Allow to render the Scene **scene** in the file named by the config,
+    /// the progress and the statistics are printed along the way
     ///
     /// ### Params
     /// **scene** The scene to render
-    /// **file_name** Target file
-    pub fn render_in(&self, scene: &Scene, file_name: &str, depth: usize, thread_count: usize) {
-        let mut buf = Vec::with_capacity(self.x * self.y * 3);
-
-        let start = std::time::Instant::now();
+    /// **config** Output file, threads, tiles, depth, integrator and passes to use
+    pub fn render_in(&self, scene: &Scene, config: &Config) -> Result<(), Box<dyn std::error::Error>> {
         println!("render scene...");
 
-        std::thread::scope(|scope| {
-            let mod_zero = (self.y % thread_count).min(1);
-            let step = self.y / thread_count + mod_zero;
-            let mut threads = vec![];
+        // progress is best effort, a closed output mustn't lose the render
+        let (buf, stats) = self.render_with(scene, config, |progress| {
+            let mut stdout = std::io::stdout();
+            let _ = write!(
+                stdout,
+                "\r{:5.1}% ({}/{} tiles), ETA {:.1} sec   ",
+                progress.ratio() * 100.0,
+                progress.done_tiles,
+                progress.total_tiles,
+                progress.eta().as_secs_f32(),
+            );
+            let _ = stdout.flush();
+        });
 
-            for thread_id in 0..thread_count {
-                let start_row = thread_id * step;
-                let stop_row = (thread_id + 1) * step;
+        // radiance is only quantized once every contributions are summed,
+        // the image is saved before anything else is printed
+        buf.write(config.output.as_str(), &config.transform, config.bit_depth)?;
 
-                threads.push(if self.flags & Camera::ANTI_ALIASING != 0 {
-                    scope.spawn(move || {
-                        let offsets = [(-0.25, -0.25), (0.25, -0.25), (-0.25, 0.25), (0.25, 0.25)];
-                        let mut buf = Vec::with_capacity(step * self.x);
+        println!();
+        println!("scene rendered in {:.2} sec!", stats.duration.as_secs_f32());
+        stats.report();
 
-                        for y in (start_row..stop_row.min(self.y)).map(|y| y as f32) {
-                            for x in (0..self.x).map(|x| x as f32) {
-                                buf.push({
-                                    let mut avg = Color::default();
+        Ok(())
+    }
 
-                                    for (ox, oy) in &offsets {
-                                        let ray = self.local_to_global_ray(&self.get_ray(x + ox, y + oy));
+    /// ### Brief
+    /// Render the Scene **scene** in memory
+    pub fn render(&self, scene: &Scene, config: &Config) -> Framebuffer {
+        self.render_with(scene, config, |_| ()).0
+    }
 
-                                        avg += match scene.closer(&ray) {
-                                            Some((object, impact)) => self.impact_color(&ray, object, &impact, scene, depth),
-                                            None => scene.background(),
-                                        } * 0.25;
-                                    }
+    /// ### Brief
+    /// Render the Scene **scene** in memory
+    ///
+    /// ### Params
+    /// **on_tile** Called from the current thread each time a tile is done
+    ///
+    /// ### Return
+    /// The rendered image along the work done to render it
+    pub fn render_with<F: FnMut(&Progress)>(&self, scene: &Scene, config: &Config, mut on_tile: F) -> (Framebuffer, Stats) {
+        let mut buf = Framebuffer::new(self.x, self.y, &config.passes);
 
-                                    avg
-                                });
-                            }
-                        }
+        let tiles = config.tile_order.tiles(self.x, self.y, config.tile_size);
+        let next_tile = AtomicUsize::new(0);
 
-                        buf
-                    })
-                } else {
-                    scope.spawn(move || {
-                        let mut buf = Vec::with_capacity(step * self.x);
-
-                        for y in (start_row..stop_row.min(self.y)).map(|y| y as f32) {
-                            for x in (0..self.x).map(|x| x as f32) {
-                                buf.push({
-                                    let ray = self.local_to_global_ray(&self.get_ray(x, y));
-
-                                    match scene.closer(&ray) {
-                                        Some((object, impact)) => self.impact_color(&ray, object, &impact, scene, depth),
-                                        None => scene.background(),
-                                    }
-                                });
+        let start = std::time::Instant::now();
+        let mut stats = Stats::default();
+
+        std::thread::scope(|scope| {
+            let (sender, receiver) = mpsc::channel();
+            let mut threads = vec![];
+
+            // a config built by hand can ask for no thread, which would leave the image black
+            for _ in 0..config.threads.max(1).min(tiles.len()) {
+                let sender = sender.clone();
+                let (tiles, next_tile) = (&tiles, &next_tile);
+
+                // every thread takes the next tile of the queue as soon as it is free
+                threads.push(scope.spawn(move || {
+                    while let Some(tile) = tiles.get(next_tile.fetch_add(1, Ordering::Relaxed)) {
+                        let mut samples = Vec::with_capacity(tile.width * tile.height);
+
+                        for y in tile.y..tile.y + tile.height {
+                            for x in tile.x..tile.x + tile.width {
+                                // seeded by pixel so the noise doesn't depend on the tiles or the threads
+                                let mut rng = SmallRng::seed_from_u64((y * self.x + x) as u64);
+                                samples.push(self.pixel_sample(x as f32, y as f32, scene, config, &mut rng));
                             }
                         }
 
-                        buf
-                    })
-                });
+                        sender.send((*tile, samples)).unwrap();
+                    }
+
+                    stats::take()
+                }));
+            }
+
+            drop(sender);
+
+            let mut progress = Progress {
+                done_tiles: 0,
+                total_tiles: tiles.len(),
+                done_pixels: 0,
+                total_pixels: self.x * self.y,
+                elapsed: Duration::ZERO,
+            };
+
+            for (tile, samples) in receiver {
+                for (id, sample) in samples.iter().enumerate() {
+                    buf.set(tile.x + id % tile.width, tile.y + id / tile.width, sample);
+                }
+
+                progress.done_tiles += 1;
+                progress.done_pixels += samples.len();
+                progress.elapsed = start.elapsed();
+                on_tile(&progress);
             }
 
             for thread in threads {
-                let partial_data = thread.join().unwrap();
+                stats.merge(&thread.join().unwrap());
+            }
+        });
+
+        stats.duration = start.elapsed();
+        (buf, stats)
+    }
 
-                buf.extend(
-                    partial_data.into_iter().flat_map(|pix| pix.to_array())
-                );
+    /// ### Brief
+    /// Shade batches of samples spread over a pixel, in adaptive mode new
+    /// batches are added while the pixel is too noisy
+    ///
+    /// ### Params
+    /// **shade** Trace the sample at the given offset from the pixel, in pixels
+    ///
+    /// ### Return
+    /// The filter weight of each sample along its value
+    fn refined_samples<F>(config: &Config, flags: u8, rng: &mut SmallRng, mut shade: F) -> Vec<(f32, Sample)>
+    where F: FnMut((f32, f32), &mut SmallRng) -> Sample {
+        // the flag keeps its historical four samples as a minimum
+        let count = match flags & Camera::ANTI_ALIASING != 0 {
+            true => config.samples.max(4),
+            false => config.samples.max(1),
+        };
+
+        let scramble = config.pattern.scramble(rng);
+        let mut samples = Vec::with_capacity(count);
+        let mut luminances = Vec::with_capacity(count);
+
+        loop {
+            for (u, v) in config.pattern.points(samples.len(), count, scramble, rng) {
+                let (offset, weight) = config.filter.sample(u, v);
+                let sample = shade(offset, rng);
+
+                luminances.push(sample.beauty.clamped().luminance());
+                samples.push((weight, sample));
             }
+
+            match config.adaptive {
+                Some(adaptive) if samples.len() + count <= adaptive.max_samples && !adaptive.converged(&luminances) => (),
+                _ => break,
+            }
+        }
+
+        samples
+    }
+
+    fn pixel_sample(&self, x: f32, y: f32, scene: &Scene, config: &Config, rng: &mut SmallRng) -> Sample {
+        let mut samples = Self::refined_samples(config, self.flags, rng, |(ox, oy), rng| {
+            let ray = self.get_ray(x + ox, y + oy);
+            let ray = match self.lens {
+                Some(lens) => self.lens_ray(&ray, &lens, rng),
+                None => ray,
+            };
+
+            let ray = self.local_to_global_ray(&ray);
+            self.trace(&ray, scene, config, rng)
         });
 
-        let dur = start.elapsed().as_secs_f32();
-        println!("scene rendered in {dur:.2} sec!");
+        // a few samples in the negative lobes of a filter can cancel the others
+        let mut total_weight: f32 = samples.iter().map(|(weight, _)| weight).sum();
+        if total_weight <= 0.0 {
+            samples.iter_mut().for_each(|(weight, _)| *weight = 1.0);
+            total_weight = samples.len() as f32;
+        }
+
+        // colors are weighted by the filter, the geometric passes come from the first ray
+        let mut samples = samples.into_iter();
+        let Some((weight, first)) = samples.next() else {
+            return Sample::background(scene.background());
+        };
+
+        let mut pixel = first.scaled(weight);
+        for (weight, sample) in samples {
+            pixel.accumulate(&sample, weight);
+        }
+
+        pixel.resolved(total_weight)
+    }
 
-        std::fs::create_dir_all(std::path::Path::new(file_name).parent().unwrap()).unwrap();
-        image::save_buffer(file_name, buf.as_slice(), self.x as u32, self.y as u32, image::ColorType::Rgb8).unwrap();
+    /// ### Brief
+    /// Follow a camera ray and gather the data of every pass
+    fn trace(&self, ray: &Ray, scene: &Scene, config: &Config, rng: &mut SmallRng) -> Sample {
+        stats::record(|stats| stats.primary_rays += 1);
+
+        let Some((id, hit)) = scene.closer_id(ray) else {
+            return Sample::background(scene.background());
+        };
+
+        let (direct, indirect) = match config.integrator {
+            Integrator::Whitted => self.impact_color(ray, &hit, scene, 0, config.depth, rng),
+            Integrator::Path => self.path_color(ray, &hit, scene, 0, config.depth, rng),
+        };
+
+        Sample {
+            beauty: direct + indirect,
+            direct, indirect,
+            albedo: hit.object.material_at(&hit).diffuse,
+            normal: hit.facing_normal(),
+            depth: self.global_to_local_point(&hit.position).z,
+            object: Some(id),
+        }
     }
 
     fn get_ray(&self, x: f32, y: f32) -> Ray {
@@ -148,64 +338,186 @@ impl Camera {
         }
     }
 
-    fn impact_color(&self, ray: &Ray, object: &dyn Object, impact: &Point, scene: &Scene, depth: usize) -> Color {
+    /// ### Brief
+    /// Move the origin of the camera space **ray** on a random point of the lens,
+    /// the ray still goes through the point it had on the focus plane
+    fn lens_ray(&self, ray: &Ray, lens: &Lens, rng: &mut SmallRng) -> Ray {
+        let (origin, vector) = (ray.origin(), ray.vector());
+        let focus = origin + vector * (lens.focus / vector.z);
+
+        let (radius, phi) = (lens.aperture * rng.gen::<f32>().sqrt(), TAU * rng.gen::<f32>());
+        let origin = origin + Point::new(radius * phi.cos(), radius * phi.sin(), 0.0);
+
+        Ray::new(origin, focus - origin).normalized()
+    }
+
+    /// ### Brief
+    /// Diffuse and specular light received at **hit** from every light of the scene,
+    /// area lights are averaged over several points of their surface
+    fn direct_light(&self, ray: &Ray, hit: &Hit, material: &Material, scene: &Scene, rng: &mut SmallRng) -> (Color, Color) {
+        let mut diffuse = Color::default();
         let mut specular = Color::default();
-        let mut reflection = Color::default();
-        let material = object.material_at(impact);
-        let mut diffuse = material.ambient * scene.ambient();
-        let normal = object.normal(impact, ray.origin());
+        let normal = hit.facing_normal();
 
         for light in scene.lights() {
-            if !light.illuminate(impact) {
+            if !light.illuminate(&hit.position) {
                 continue;
             }
 
-            let vec_light = light.vec_to_light(impact);
-            let alpha = vec_light.dot(normal.vector());
-            if alpha <= 0.0 {
-                continue;
-            }
+            let count = light.shadow_rays();
+            let intensity = light.intensity(&hit.position) / count as f32;
 
-            let shadow = if (self.flags & Camera::NO_SHADOW) != 0 {
-                Color::new_gray(255)
-            } else {
-                scene.light_filter(impact, light.as_ref(), 0)
-            };
+            for id in 0..count {
+                // stratified along the first dimension to spread the samples
+                let u1 = (id as f32 + rng.gen::<f32>()) / count as f32;
+
+                let (vec_light, weight, target) = match light.sample(&hit.position, u1, rng.gen()) {
+                    Some((target, weight)) => ((target - hit.position).normalized(), weight * intensity, Some(target)),
+                    None => (light.vec_to_light(&hit.position), intensity, None),
+                };
+
+                let alpha = vec_light.dot(&normal);
+                if alpha <= 0.0 || weight <= 0.0 {
+                    continue;
+                }
 
-            diffuse += material.diffuse * light.diffuse() * alpha * shadow;
-            specular += material.specular * (normal.vector() * 2.0 * alpha - vec_light).dot(&-ray.vector()).powf(material.shininess) * light.specular() * alpha * shadow;
+                let shadow = if (self.flags & Camera::NO_SHADOW) != 0 {
+                    Color::WHITE
+                } else if let Some(target) = target {
+                    scene.point_filter(hit, &target, 0)
+                } else {
+                    scene.light_filter(hit, light.as_ref(), 0)
+                };
+
+                diffuse += material.diffuse * light.diffuse() * alpha * shadow * weight;
+                specular += material.specular * (normal * 2.0 * alpha - vec_light).dot(&-ray.vector()).max(0.0).powf(material.shininess) * light.specular() * alpha * shadow * weight;
+            }
         }
 
-        if depth > 0 {
+        (diffuse, specular)
+    }
+
+    /// ### Brief
+    /// Whitted estimation of the light leaving **hit** toward the ray origin
+    ///
+    /// ### Params
+    /// **bounce** Number of reflections and refractions already done by the ray
+    /// **max_depth** Maximum number of reflections and refractions of the ray
+    ///
+    /// ### Return
+    /// The light coming straight from the light sources and the one
+    /// brought by the ambient, reflections and refractions
+    fn impact_color(&self, ray: &Ray, hit: &Hit, scene: &Scene, bounce: usize, max_depth: usize, rng: &mut SmallRng) -> (Color, Color) {
+        stats::record(|stats| stats.max_depth = stats.max_depth.max(bounce));
+        let material = hit.object.material_at(hit);
+        let (mut direct, specular) = self.direct_light(ray, hit, &material, scene, rng);
+        let mut indirect = material.ambient * scene.ambient();
+
+        if bounce < max_depth {
             if material.alpha < 255 {
-                let refraction_ray = object.refracted_ray(ray, impact);
+                let refraction_ray = hit.object.refracted_ray(ray, hit);
                 let closer = scene.closer(&refraction_ray);
+                stats::record(|stats| stats.refraction_rays += 1);
 
                 let coef_refraction = material.alpha as f32 / 255.0;
-                diffuse = diffuse * coef_refraction + match closer {
+                let refraction = match closer {
                     None => scene.background(),
-                    Some((object, impact)) => {
-                        self.impact_color(&refraction_ray, object, &impact, scene, depth - 1)
+                    Some(hit) => {
+                        let (direct, indirect) = self.impact_color(&refraction_ray, &hit, scene, bounce + 1, max_depth, rng);
+                        direct + indirect
                     }
-                } * (1.0 - coef_refraction);
+                };
+
+                direct = direct * coef_refraction;
+                indirect = indirect * coef_refraction + refraction * (1.0 - coef_refraction);
             }
 
             if material.reflection > 0 {
-                let reflected_ray = object.reflected_ray(ray, impact);
+                let reflected_ray = hit.object.reflected_ray(ray, hit);
                 let closer = scene.closer(&reflected_ray);
+                stats::record(|stats| stats.reflection_rays += 1);
 
                 let coef_reflection = material.reflection as f32 / 255.0;
-                reflection = match closer {
+                let reflection = match closer {
                     None => scene.background(),
-                    Some((object, impact)) => {
-                        self.impact_color(&reflected_ray, object, &impact, scene, depth - 1)
+                    Some(hit) => {
+                        let (direct, indirect) = self.impact_color(&reflected_ray, &hit, scene, bounce + 1, max_depth, rng);
+                        direct + indirect
                     }
-                } * coef_reflection;
-                diffuse = diffuse * (1.0 - coef_reflection);
+                };
+
+                direct = direct * (1.0 - coef_reflection);
+                indirect = indirect * (1.0 - coef_reflection) + reflection * coef_reflection;
             }
         }
 
-        diffuse + specular + reflection
+        (direct + specular, indirect)
+    }
+
+    /// ### Brief
+    /// Monte Carlo estimation of the light leaving **hit** toward the ray origin
+    ///
+    /// ### Params
+    /// **bounce** Number of bounces already done by the path
+    /// **max_depth** Maximum number of bounces of the path
+    ///
+    /// ### Return
+    /// The light sampled from the light sources at this impact and the one
+    /// gathered by the rest of the path
+    fn path_color(&self, ray: &Ray, hit: &Hit, scene: &Scene, bounce: usize, max_depth: usize, rng: &mut SmallRng) -> (Color, Color) {
+        stats::record(|stats| stats.max_depth = stats.max_depth.max(bounce));
+        let material = hit.object.material_at(hit);
+
+        // one of the reflection, refraction or diffuse lobe is picked with the
+        // probability of its weight, so its contribution doesn't need scaling
+        let reflection = material.reflection as f32 / 255.0;
+        let refraction = (1.0 - material.alpha as f32 / 255.0) * (1.0 - reflection);
+        let event = rng.gen::<f32>();
+
+        let (next_ray, throughput, direct) = if event < reflection {
+            (hit.object.reflected_ray(ray, hit), Color::WHITE, Color::default())
+        } else if event < reflection + refraction {
+            (hit.object.refracted_ray(ray, hit), Color::WHITE, Color::default())
+        } else {
+            // next event estimation, the light sources are sampled directly
+            let (diffuse, specular) = self.direct_light(ray, hit, &material, scene, rng);
+            let normal = hit.facing_normal();
+            let vector = cosine_hemisphere(&normal, rng.gen(), rng.gen());
+
+            (Ray::new(hit.position + hit.facing_geometric_normal() * GAP, vector), material.diffuse, diffuse + specular)
+        };
+
+        if bounce >= max_depth {
+            return (direct, Color::default());
+        }
+
+        // russian roulette, dark paths are stopped early while the survivors are boosted
+        let mut throughput = throughput;
+        if bounce >= RUSSIAN_ROULETTE_BOUNCE {
+            let survival = throughput.max_channel().clamp(0.05, 1.0);
+            if rng.gen::<f32>() > survival {
+                return (direct, Color::default());
+            }
+
+            throughput = throughput * (1.0 / survival);
+        }
+
+        // diffuse bounces are counted along the mirror reflections
+        let refracted = (reflection..reflection + refraction).contains(&event);
+        stats::record(|stats| match refracted {
+            true => stats.refraction_rays += 1,
+            false => stats.reflection_rays += 1,
+        });
+
+        let incoming = match scene.closer(&next_ray) {
+            Some(next_hit) => {
+                let (direct, indirect) = self.path_color(&next_ray, &next_hit, scene, bounce + 1, max_depth, rng);
+                direct + indirect
+            }
+            None => scene.background(),
+        };
+
+        (direct, throughput * incoming)
     }
 }
 
@@ -274,7 +586,7 @@ impl<'de> Deserialize<'de> for Focal {
 
 impl<'de> Deserialize<'de> for Camera {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error> where D: Deserializer<'de> {
-        const FIELDS: &[&str] = &["size", "focal", "flags", "transform", "rotate", "scale"];
+        const FIELDS: &[&str] = &["size", "focal", "aperture", "focus", "flags", "transform", "rotate", "scale", "shear", "matrix", "transforms"];
         const FLAGS: &[&str] = &["ANTI_ALIASING", "NO_SHADOW"];
         struct CameraVisitor;
 
@@ -288,20 +600,21 @@ impl<'de> Deserialize<'de> for Camera {
             fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error> where A: MapAccess<'de> {
                 let mut size: Option<[usize; 2]> = None;
                 let mut focal = None;
+                let mut aperture: Option<f32> = None;
+                let mut focus: Option<f32> = None;
                 let mut flags: Option<Vec<&str>> = None;
-                let mut transform = None;
-                let mut rotate = None;
-                let mut scale = None;
+                let mut placement = Placement::default();
 
                 while let Some(field) = map.next_key()? {
                     match field {
                         "size" => size = Some(map.next_value()?),
                         "focal" => focal = Some(map.next_value()?),
+                        "aperture" => aperture = Some(map.next_value()?),
+                        "focus" => focus = Some(map.next_value()?),
                         "flags" => flags = Some(map.next_value()?),
-                        "transform" => transform = Some(map.next_value()?),
-                        "rotate" => rotate = Some(map.next_value()?),
-                        "scale" => scale = Some(map.next_value()?),
-                        _ => return Err(Error::unknown_field(field, FIELDS)),
+                        _ => if !placement.visit_field(field, &mut map)? {
+                            return Err(Error::unknown_field(field, FIELDS));
+                        }
                     }
                 }
 
@@ -314,10 +627,9 @@ impl<'de> Deserialize<'de> for Camera {
                         let mut flag = 0;
 
                         for entree in flags {
-                            match entree {
-                                "ANTI_ALIASING" => flag |= Camera::ANTI_ALIASING,
-                                "NO_SHADOW" => flag |= Camera::NO_SHADOW,
-                                _ => return Err(Error::unknown_variant(entree, FLAGS)),
+                            match Camera::flag(entree) {
+                                Some(value) => flag |= value,
+                                None => return Err(Error::unknown_variant(entree, FLAGS)),
                             }
                         }
 
@@ -325,22 +637,26 @@ impl<'de> Deserialize<'de> for Camera {
                     }
                 };
 
-                let mut camera = Camera::new(x, y, focal);
-                camera.set_flags(flags);
+                let lens = match aperture {
+                    Some(aperture) if aperture < 0.0 => {
+                        return Err(Error::invalid_value(Unexpected::Float(aperture as f64), &"a positive aperture"));
+                    }
+                    Some(aperture) if aperture > 0.0 => {
+                        let focus = focus.ok_or_else(|| Error::missing_field("focus"))?;
+                        if focus <= 0.0 {
+                            return Err(Error::invalid_value(Unexpected::Float(focus as f64), &"a strictly positive focus distance"));
+                        }
 
-                if let Some(Point {x, y , z}) = transform {
-                    camera.move_global(x, y, z);
-                }
+                        Some(Lens { aperture, focus })
+                    }
+                    _ => None,
+                };
 
-                if let Some(Point {x, y , z}) = rotate {
-                    camera.rotate_x(x);
-                    camera.rotate_y(y);
-                    camera.rotate_z(z);
-                }
+                let mut camera = Camera::new(x, y, focal);
+                camera.set_flags(flags);
+                camera.set_lens(lens);
 
-                if let Some(scale) = scale {
-                    camera.scale(scale);
-                }
+                camera.transform_global(&placement.matrix().map_err(Error::custom)?);
 
                 Ok(camera)
             }
@@ -349,3 +665,37 @@ impl<'de> Deserialize<'de> for Camera {
         deserializer.deserialize_map(CameraVisitor)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sampler::Adaptive;
+
+    #[test]
+    fn noisy_pixel_is_refined_at_new_positions() {
+        let config = Config {
+            adaptive: Some(Adaptive { max_samples: 16, threshold: 0.01 }),
+            ..Config::default()
+        };
+        assert_eq!(config.pattern, crate::sampler::Pattern::Regular);
+
+        let mut rng = SmallRng::seed_from_u64(0);
+        let mut offsets = Vec::new();
+
+        // black and white samples in turn never converge
+        let samples = Camera::refined_samples(&config, 0, &mut rng, |offset, _rng| {
+            offsets.push(offset);
+            match offsets.len() % 2 {
+                0 => Sample::background(Color::BLACK),
+                _ => Sample::background(Color::WHITE),
+            }
+        });
+
+        assert!(samples.len() > 1);
+
+        let mut distinct = offsets.clone();
+        distinct.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        distinct.dedup();
+        assert!(distinct.len() > 1, "every sample was taken at {:?}", offsets[0]);
+    }
+}
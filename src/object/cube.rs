@@ -0,0 +1,115 @@
+use crate::material::{MatProvider, Material};
+use crate::object::{Movable, Object, hit::Hit};
+use crate::math::{
+    bounding_box::BoundingBox,
+    point::Point,
+    ray::Ray
+};
+
+use rulinalg::matrix::Matrix;
+
+/// Cube [-1, 1] on each axis, scale it to get any box
+pub struct Cube {
+    tra: Matrix<f32>,
+    inv: Matrix<f32>,
+
+    mat: Box<dyn MatProvider>,
+    coef_refraction: f32,
+}
+
+impl Cube {
+    pub fn new(mat: Box<dyn MatProvider>, coef_refraction: f32) -> Self {
+        Self {
+            tra: Matrix::identity(4),
+            inv: Matrix::identity(4),
+            coef_refraction,
+            mat,
+        }
+    }
+}
+
+impl Cube {
+    /// ### Brief
+    /// Face holding the point **local** of the surface, the one of its
+    /// coordinates closest to ±1
+    ///
+    /// ### Return
+    /// Index of the face, in the order +x, -x, +y, -y, +z, -z, and its outward normal
+    fn face(local: &Point) -> (usize, Point) {
+        let (x, y, z) = (local.x.abs(), local.y.abs(), local.z.abs());
+
+        if x >= y && x >= z {
+            (if local.x > 0.0 { 0 } else { 1 }, Point::new(local.x.signum(), 0.0, 0.0))
+        } else if y >= z {
+            (if local.y > 0.0 { 2 } else { 3 }, Point::new(0.0, local.y.signum(), 0.0))
+        } else {
+            (if local.z > 0.0 { 4 } else { 5 }, Point::new(0.0, 0.0, local.z.signum()))
+        }
+    }
+}
+
+impl Movable for Cube {
+    fn tra(&self) -> &Matrix<f32> {
+        &self.tra
+    }
+
+    fn tra_mut(&mut self) -> &mut Matrix<f32> {
+        &mut self.tra
+    }
+
+    fn inv(&self) -> &Matrix<f32> {
+        &self.inv
+    }
+
+    fn inv_mut(&mut self) -> &mut Matrix<f32> {
+        &mut self.inv
+    }
+}
+
+impl Object for Cube {
+    fn intersect(&self, ray: &Ray) -> Option<Hit<'_>> {
+        let local_ray = self.global_to_local_ray(ray);
+        let (near, far) = BoundingBox::new(Point::new(-1.0, -1.0, -1.0), Point::new(1.0, 1.0, 1.0))
+            .intersect(&local_ray)?;
+
+        // from inside the cube the ray only meets the face it leaves by
+        let t = if near > 0.0 { near } else { far };
+        if t <= 0.0 {
+            return None;
+        }
+
+        let local = local_ray.origin() + local_ray.vector() * t;
+        let (face, normal) = Self::face(&local);
+
+        // each face is mapped on the whole texture, upright when seen from outside
+        let (u, v) = match face {
+            0 => (local.z, -local.y),
+            1 => (-local.z, -local.y),
+            2 => (local.x, -local.z),
+            3 => (-local.x, -local.z),
+            4 => (-local.x, -local.y),
+            _ => (local.x, -local.y),
+        };
+        let uv = (((u + 1.0) / 2.0).clamp(0.0, 1.0), ((v + 1.0) / 2.0).clamp(0.0, 1.0));
+
+        let normal = self.local_to_global_normal(&normal);
+        let mut hit = Hit::new(self, ray, t, local, normal, uv);
+        hit.primitive = face;
+
+        Some(hit)
+    }
+
+    fn material_at(&self, hit: &Hit) -> Material {
+        let (x, y) = hit.uv;
+        self.mat.material(x, y)
+    }
+
+    fn coef_refraction(&self) -> f32 {
+        self.coef_refraction
+    }
+
+    fn bounding_box(&self) -> Option<BoundingBox> {
+        let bbox = BoundingBox::new(Point::new(-1.0, -1.0, -1.0), Point::new(1.0, 1.0, 1.0));
+        Some(bbox.transform(self.tra()))
+    }
+}
@@ -0,0 +1,116 @@
+use crate::material::{MatProvider, Material};
+use crate::object::{Movable, Object, hit::Hit};
+use crate::math::{
+    bounding_box::BoundingBox,
+    point::Point,
+    ray::Ray,
+    roots::quadratic,
+};
+
+use rulinalg::matrix::Matrix;
+use std::f32::consts::TAU;
+
+/// Cylinder of radius 1 around the y axis, between y = -1 and y = 1
+pub struct Cylinder {
+    tra: Matrix<f32>,
+    inv: Matrix<f32>,
+
+    mat: Box<dyn MatProvider>,
+    coef_refraction: f32,
+    /// Closed by a disk at each end, otherwise it is an open tube
+    capped: bool,
+}
+
+impl Cylinder {
+    pub fn new(mat: Box<dyn MatProvider>, coef_refraction: f32, capped: bool) -> Self {
+        Self {
+            tra: Matrix::identity(4),
+            inv: Matrix::identity(4),
+            coef_refraction,
+            mat, capped,
+        }
+    }
+}
+
+impl Movable for Cylinder {
+    fn tra(&self) -> &Matrix<f32> {
+        &self.tra
+    }
+
+    fn tra_mut(&mut self) -> &mut Matrix<f32> {
+        &mut self.tra
+    }
+
+    fn inv(&self) -> &Matrix<f32> {
+        &self.inv
+    }
+
+    fn inv_mut(&mut self) -> &mut Matrix<f32> {
+        &mut self.inv
+    }
+}
+
+impl Object for Cylinder {
+    fn intersect(&self, ray: &Ray) -> Option<Hit<'_>> {
+        let local_ray = self.global_to_local_ray(ray);
+        let (origin, vector) = local_ray.consume();
+        let mut closer: Option<(f32, Point, bool)> = None;
+
+        let mut keep = |t: f32, normal: Point, cap: bool| {
+            if t > 0.0 && closer.is_none_or(|(closer, _, _)| t < closer) {
+                closer = Some((t, normal, cap));
+            }
+        };
+
+        let a = vector.x * vector.x + vector.z * vector.z;
+        let b = 2.0 * (origin.x * vector.x + origin.z * vector.z);
+        let c = origin.x * origin.x + origin.z * origin.z - 1.0;
+
+        if let Some((t1, t2)) = quadratic(a, b, c) {
+            for t in [t1, t2] {
+                let local = origin + vector * t;
+                if local.y.abs() <= 1.0 {
+                    keep(t, Point::new(local.x, 0.0, local.z), false);
+                }
+            }
+        }
+
+        if self.capped && vector.y != 0.0 {
+            for y in [-1.0, 1.0] {
+                let t = (y - origin.y) / vector.y;
+                let local = origin + vector * t;
+                if local.x * local.x + local.z * local.z <= 1.0 {
+                    keep(t, Point::new(0.0, y, 0.0), true);
+                }
+            }
+        }
+
+        let (t, normal, cap) = closer?;
+        let local = origin + vector * t;
+        let normal = self.local_to_global_normal(&normal);
+
+        // the side wraps the u axis around, v goes down the side and
+        // from the center to the rim on the caps
+        let u = local.z.atan2(local.x) / TAU + 0.5;
+        let v = match cap {
+            false => (1.0 - local.y) / 2.0,
+            true => (local.x * local.x + local.z * local.z).sqrt().min(1.0),
+        };
+
+        Some(Hit::new(self, ray, t, local, normal, (u, v)))
+    }
+
+    fn material_at(&self, hit: &Hit) -> Material {
+        let (x, y) = hit.uv;
+        self.mat.material(x, y)
+    }
+
+    fn coef_refraction(&self) -> f32 {
+        self.coef_refraction
+    }
+
+    fn bounding_box(&self) -> Option<BoundingBox> {
+        let bbox = BoundingBox::new(Point::new(-1.0, -1.0, -1.0), Point::new(1.0, 1.0, 1.0));
+        Some(bbox.transform(self.tra()))
+    }
+}
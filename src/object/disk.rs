@@ -0,0 +1,82 @@
+use crate::material::{MatProvider, Material};
+use crate::object::{Movable, Object, hit::Hit};
+use crate::math::{
+    bounding_box::BoundingBox,
+    point::Point,
+    ray::Ray
+};
+
+use rulinalg::matrix::Matrix;
+use std::f32::consts::TAU;
+
+/// Disk of radius 1 of the plane z = 0, facing +z
+pub struct Disk {
+    tra: Matrix<f32>,
+    inv: Matrix<f32>,
+
+    mat: Box<dyn MatProvider>,
+    coef_refraction: f32,
+}
+
+impl Disk {
+    pub fn new(mat: Box<dyn MatProvider>, coef_refraction: f32) -> Self {
+        Self {
+            tra: Matrix::identity(4),
+            inv: Matrix::identity(4),
+            coef_refraction,
+            mat,
+        }
+    }
+}
+
+impl Movable for Disk {
+    fn tra(&self) -> &Matrix<f32> {
+        &self.tra
+    }
+
+    fn tra_mut(&mut self) -> &mut Matrix<f32> {
+        &mut self.tra
+    }
+
+    fn inv(&self) -> &Matrix<f32> {
+        &self.inv
+    }
+
+    fn inv_mut(&mut self) -> &mut Matrix<f32> {
+        &mut self.inv
+    }
+}
+
+impl Object for Disk {
+    fn intersect(&self, ray: &Ray) -> Option<Hit<'_>> {
+        let local_ray = self.global_to_local_ray(ray);
+
+        let t = -local_ray.origin().z / local_ray.vector().z;
+        let local = local_ray.origin() + local_ray.vector() * t;
+        let radius = (local.x * local.x + local.y * local.y).sqrt();
+
+        if t > 0.0 && radius <= 1.0 {
+            // u turns around the center and v goes toward the rim
+            let u = local.y.atan2(local.x) / TAU + 0.5;
+            let normal = self.local_to_global_normal(&Point::new(0.0, 0.0, 1.0));
+
+            Some(Hit::new(self, ray, t, local, normal, (u, radius)))
+        } else {
+            None
+        }
+    }
+
+    fn material_at(&self, hit: &Hit) -> Material {
+        let (x, y) = hit.uv;
+        self.mat.material(x, y)
+    }
+
+    fn coef_refraction(&self) -> f32 {
+        self.coef_refraction
+    }
+
+    fn bounding_box(&self) -> Option<BoundingBox> {
+        let bbox = BoundingBox::new(Point::new(-1.0, -1.0, 0.0), Point::new(1.0, 1.0, 0.0));
+        Some(bbox.transform(self.tra()))
+    }
+}
@@ -0,0 +1,128 @@
+use crate::material::Material;
+use crate::object::{Movable, Object, hit::Hit, instance::Definitions};
+use crate::bvh::Bvh;
+use crate::stats;
+use crate::math::{
+    bounding_box::BoundingBox,
+    ray::Ray
+};
+
+use rulinalg::matrix::Matrix;
+
+/// Objects moved together, each child transform is relative to the group
+pub struct Group {
+    tra: Matrix<f32>,
+    inv: Matrix<f32>,
+
+    children: Vec<Box<dyn Object>>,
+    bvh: Option<Bvh>,
+}
+
+impl Group {
+    /// ### Brief
+    /// Gather **children**, already placed in the group space, every
+    /// child is tested against every ray until `build_bvh` is called
+    pub fn new(children: Vec<Box<dyn Object>>) -> Self {
+        Self {
+            tra: Matrix::identity(4),
+            inv: Matrix::identity(4),
+            children,
+            bvh: None,
+        }
+    }
+
+    /// Closest impact of **ray**, given in the group space, on the children
+    fn closer<'a>(&'a self, ray: &Ray) -> Option<Hit<'a>> {
+        if let Some(bvh) = &self.bvh {
+            return bvh.closer(ray, |id| {
+                stats::record(|stats| stats.intersection_tests += 1);
+                self.children[id].intersect(ray).map(|hit| (hit.t, hit))
+            }).map(|(_id, hit)| hit);
+        }
+
+        let mut closer: Option<Hit> = None;
+        stats::record(|stats| stats.intersection_tests += self.children.len() as u64);
+
+        for child in self.children.iter() {
+            if let Some(hit) = child.intersect(ray) {
+                if closer.as_ref().is_none_or(|closer| hit.t < closer.t) {
+                    closer = Some(hit);
+                }
+            }
+        }
+
+        closer
+    }
+}
+
+impl Movable for Group {
+    fn tra(&self) -> &Matrix<f32> {
+        &self.tra
+    }
+
+    fn tra_mut(&mut self) -> &mut Matrix<f32> {
+        &mut self.tra
+    }
+
+    fn inv(&self) -> &Matrix<f32> {
+        &self.inv
+    }
+
+    fn inv_mut(&mut self) -> &mut Matrix<f32> {
+        &mut self.inv
+    }
+}
+
+impl Object for Group {
+    /// ### Brief
+    /// Closest impact on the children, the hit reports the child so
+    /// its material and refraction are used, the local impact stays
+    /// in the child space
+    fn intersect(&self, ray: &Ray) -> Option<Hit<'_>> {
+        let local_ray = self.global_to_local_ray(ray);
+        let mut hit = self.closer(&local_ray)?;
+
+        // an affine transform keeps the ray coefficient, and the side of the
+        // surface the ray comes from as normals follow the inverse-transpose
+        hit.position = ray.origin() + ray.vector() * hit.t;
+        hit.normal = self.local_to_global_normal(&hit.normal).normalized();
+        hit.geometric_normal = self.local_to_global_normal(&hit.geometric_normal).normalized();
+
+        Some(hit)
+    }
+
+    fn material_at(&self, hit: &Hit) -> Material {
+        hit.object.material_at(hit)
+    }
+
+    /// The group itself has no surface, its children refract on their own
+    fn coef_refraction(&self) -> f32 {
+        1.0
+    }
+
+    fn bounding_box(&self) -> Option<BoundingBox> {
+        // a single unbounded child, like a plane, makes the whole group unbounded
+        let bounds = self.children.iter()
+            .map(|child| child.bounding_box())
+            .try_fold(BoundingBox::empty(), |bounds, bbox| bbox.map(|bbox| bounds.union(&bbox)))?;
+
+        Some(bounds.transform(self.tra()))
+    }
+
+    fn resolve(&mut self, definitions: &Definitions) -> Result<(), String> {
+        for child in self.children.iter_mut() {
+            child.resolve(definitions)?;
+        }
+
+        Ok(())
+    }
+
+    fn build_bvh(&mut self) {
+        for child in self.children.iter_mut() {
+            child.build_bvh();
+        }
+
+        let boxes: Vec<_> = self.children.iter().map(|child| child.bounding_box()).collect();
+        self.bvh = Some(Bvh::new(&boxes));
+    }
+}
@@ -0,0 +1,74 @@
+use crate::object::Object;
+use crate::math::{
+    point::Point,
+    ray::Ray,
+};
+
+/// Everything known about the impact of a ray on an object
+#[derive(Clone, Copy)]
+pub struct Hit<'a> {
+    /// Object hit by the ray
+    pub object: &'a dyn Object,
+    /// Ray coefficient of the impact, `origin + vector * t`
+    pub t: f32,
+
+    /// Impact in the object space
+    pub local: Point,
+    /// Impact in the world space
+    pub position: Point,
+    /// Normalized outward normal in the world space, used for shading
+    pub normal: Point,
+    /// Normalized outward normal of the actual surface in the world space, it
+    /// only differs from **normal** on interpolated surfaces like smooth meshes
+    pub geometric_normal: Point,
+    /// Texture coordinates given to `MatProvider::material`
+    pub uv: (f32, f32),
+
+    /// True when the ray reached the outer side of the surface
+    pub front_face: bool,
+    /// Sub part of the object that was hit, like the triangle of a mesh
+    pub primitive: usize,
+}
+
+impl<'a> Hit<'a> {
+    /// ### Brief
+    /// Build the hit record of **ray** on **object**
+    ///
+    /// ### Params
+    /// **t** Ray coefficient of the impact
+    /// **local** Impact in the object space
+    /// **normal** Outward normal in the world space, not necessarily normalized
+    /// **uv** Texture coordinates of the impact
+    pub fn new(object: &'a dyn Object, ray: &Ray, t: f32, local: Point, normal: Point, uv: (f32, f32)) -> Self {
+        let normal = normal.normalized();
+        let front_face = ray.vector().dot(&normal) < 0.0;
+
+        Self {
+            object, t, local,
+            position: ray.origin() + ray.vector() * t,
+            normal, uv, front_face,
+            geometric_normal: normal,
+            primitive: 0,
+        }
+    }
+
+    /// Normal on the side of the surface the ray came from
+    pub fn facing_normal(&self) -> Point {
+        if self.front_face {
+            self.normal
+        } else {
+            -self.normal
+        }
+    }
+
+    /// ### Brief
+    /// Geometric normal on the side of the surface the ray came from,
+    /// rays leaving that side are offset along it to avoid hitting the surface again
+    pub fn facing_geometric_normal(&self) -> Point {
+        if self.front_face {
+            self.geometric_normal
+        } else {
+            -self.geometric_normal
+        }
+    }
+}
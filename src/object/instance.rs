@@ -0,0 +1,168 @@
+use crate::material::{MatProvider, Material};
+use crate::object::{Movable, Object, hit::Hit};
+use crate::parser::Acceleration;
+use crate::math::{
+    bounding_box::BoundingBox,
+    ray::Ray
+};
+
+use rulinalg::matrix::Matrix;
+use std::{collections::HashMap, sync::Arc};
+
+/// Named objects of a scene, shared by the instances naming them
+#[derive(Default)]
+pub struct Definitions {
+    objects: HashMap<String, Arc<dyn Object>>,
+}
+
+impl Definitions {
+    /// ### Brief
+    /// Resolve the instances of each definition, in whatever order they are
+    /// given, a definition can name the ones given after it
+    ///
+    /// ### Params
+    /// **acceleration** Whether the groups of the definitions get their bounding volume hierarchy
+    ///
+    /// ### Return
+    /// An error message when an instance names a missing definition or when
+    /// definitions instance each other
+    pub fn new(pending: Vec<(String, Box<dyn Object>)>, acceleration: Acceleration) -> Result<Self, String> {
+        let mut definitions = Definitions::default();
+        let mut pending = pending;
+
+        while !pending.is_empty() {
+            let count = pending.len();
+            let mut waiting = Vec::new();
+            let mut missing = Vec::new();
+
+            for (name, mut object) in pending {
+                match object.resolve(&definitions) {
+                    Ok(()) => {
+                        // shared from now on, the hierarchies can't be built later
+                        if acceleration == Acceleration::Bvh {
+                            object.build_bvh();
+                        }
+                        definitions.insert(&name, object);
+                    }
+                    Err(name_missing) => {
+                        missing.push(name_missing);
+                        waiting.push((name, object));
+                    }
+                }
+            }
+
+            // nothing could be resolved, either a name is unknown or the waiting ones name each other
+            if waiting.len() == count {
+                return match missing.iter().find(|name| waiting.iter().all(|(other, _)| other != *name)) {
+                    Some(name) => Err(format!("unknown definition `{}`", name)),
+                    None => Err(format!("definition `{}` ends up instancing itself", missing[0])),
+                };
+            }
+
+            pending = waiting;
+        }
+
+        Ok(definitions)
+    }
+
+    /// Register **object** under **name**, replacing any previous definition
+    pub fn insert(&mut self, name: &str, object: Box<dyn Object>) {
+        self.objects.insert(name.to_owned(), Arc::from(object));
+    }
+
+    /// Object registered under **name**
+    pub fn get(&self, name: &str) -> Option<Arc<dyn Object>> {
+        self.objects.get(name).cloned()
+    }
+}
+
+/// Copy of a shared object with its own transform and, possibly, material
+pub struct Instance {
+    tra: Matrix<f32>,
+    inv: Matrix<f32>,
+
+    /// Definition drawn, only known once resolved
+    name: String,
+    object: Option<Arc<dyn Object>>,
+
+    mat: Option<Box<dyn MatProvider>>,
+    coef_refraction: Option<f32>,
+}
+
+impl Instance {
+    /// ### Brief
+    /// Place a new copy of the definition **name**, nothing is drawn until
+    /// it is found by `resolve`
+    ///
+    /// ### Params
+    /// **mat** Material replacing the ones of the object, `None` to keep them
+    /// **coef_refraction** Refraction used with **mat**, the one of the object when `None`,
+    /// ignored without **mat**
+    pub fn new(name: &str, mat: Option<Box<dyn MatProvider>>, coef_refraction: Option<f32>) -> Self {
+        Self {
+            tra: Matrix::identity(4),
+            inv: Matrix::identity(4),
+            name: name.to_owned(),
+            object: None,
+            mat, coef_refraction,
+        }
+    }
+}
+
+impl Movable for Instance {
+    fn tra(&self) -> &Matrix<f32> {
+        &self.tra
+    }
+
+    fn tra_mut(&mut self) -> &mut Matrix<f32> {
+        &mut self.tra
+    }
+
+    fn inv(&self) -> &Matrix<f32> {
+        &self.inv
+    }
+
+    fn inv_mut(&mut self) -> &mut Matrix<f32> {
+        &mut self.inv
+    }
+}
+
+impl Object for Instance {
+    fn intersect(&self, ray: &Ray) -> Option<Hit<'_>> {
+        let local_ray = self.global_to_local_ray(ray);
+        let mut hit = self.object.as_ref()?.intersect(&local_ray)?;
+
+        hit.position = ray.origin() + ray.vector() * hit.t;
+        hit.normal = self.local_to_global_normal(&hit.normal).normalized();
+        hit.geometric_normal = self.local_to_global_normal(&hit.geometric_normal).normalized();
+
+        // without override the shared object keeps giving its own materials
+        if self.mat.is_some() {
+            hit.object = self;
+        }
+
+        Some(hit)
+    }
+
+    fn material_at(&self, hit: &Hit) -> Material {
+        let (x, y) = hit.uv;
+
+        match &self.mat {
+            Some(mat) => mat.material(x, y),
+            None => hit.object.material_at(hit),
+        }
+    }
+
+    fn coef_refraction(&self) -> f32 {
+        self.coef_refraction.unwrap_or_else(|| self.object.as_ref().map_or(1.0, |object| object.coef_refraction()))
+    }
+
+    fn bounding_box(&self) -> Option<BoundingBox> {
+        self.object.as_ref()?.bounding_box().map(|bbox| bbox.transform(self.tra()))
+    }
+
+    fn resolve(&mut self, definitions: &Definitions) -> Result<(), String> {
+        self.object = Some(definitions.get(&self.name).ok_or_else(|| self.name.clone())?);
+        Ok(())
+    }
+}
@@ -0,0 +1,124 @@
+use crate::math::point::Point;
+use crate::material::Color;
+use crate::object::{
+    light::Light,
+    Movable,
+};
+
+use rulinalg::matrix::Matrix;
+use std::f32::consts::TAU;
+
+/// Surface of an area light, in its local space
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Shape {
+    /// Square [-1, 1] x [-1, 1] of the plane z = 0, lighting toward +z
+    Rectangle,
+    /// Disk of radius 1 of the plane z = 0, lighting toward +z
+    Disk,
+    /// Sphere of radius 1, lighting all around
+    Sphere,
+}
+
+/// Light emitted by a surface, its shadows get soft edges
+pub struct AreaLight {
+    tra: Matrix<f32>,
+    inv: Matrix<f32>,
+
+    shape: Shape,
+    diffuse: Color,
+    specular: Color,
+    samples: usize,
+}
+
+impl AreaLight {
+    /// ### Brief
+    /// Build an area light
+    ///
+    /// ### Params
+    /// **samples** Number of shadow rays shot toward the light from each shaded point
+    pub fn new(shape: Shape, diffuse: Color, specular: Color, samples: usize) -> Self {
+        Self {
+            tra: Matrix::identity(4),
+            inv: Matrix::identity(4),
+            shape, diffuse, specular, samples,
+        }
+    }
+}
+
+impl Movable for AreaLight {
+    fn tra(&self) -> &Matrix<f32> {
+        &self.tra
+    }
+
+    fn tra_mut(&mut self) -> &mut Matrix<f32> {
+        &mut self.tra
+    }
+
+    fn inv(&self) -> &Matrix<f32> {
+        &self.inv
+    }
+
+    fn inv_mut(&mut self) -> &mut Matrix<f32> {
+        &mut self.inv
+    }
+}
+
+impl Light for AreaLight {
+    fn illuminate(&self, point: &Point) -> bool {
+        match self.shape {
+            Shape::Rectangle | Shape::Disk => self.global_to_local_point(point).z > 0.0,
+            Shape::Sphere => true,
+        }
+    }
+
+    fn diffuse(&self) -> Color {
+        self.diffuse
+    }
+
+    fn specular(&self) -> Color {
+        self.specular
+    }
+
+    fn shadow_rays(&self) -> usize {
+        self.samples
+    }
+
+    fn sample(&self, point: &Point, u1: f32, u2: f32) -> Option<(Point, f32)> {
+        let local = self.global_to_local_point(point);
+
+        let (sample, normal) = match self.shape {
+            Shape::Rectangle => {
+                let sample = Point::new(u1 * 2.0 - 1.0, u2 * 2.0 - 1.0, 0.0);
+                (sample, Point::new(0.0, 0.0, 1.0))
+            }
+            Shape::Disk => {
+                let (radius, phi) = (u1.sqrt(), TAU * u2);
+                let sample = Point::new(radius * phi.cos(), radius * phi.sin(), 0.0);
+                (sample, Point::new(0.0, 0.0, 1.0))
+            }
+            Shape::Sphere => {
+                // only the half facing the point can light it
+                let axis = local.normalized();
+                let (tangent, bitangent) = crate::math::sampling::orthonormal_basis(&axis);
+                let (radius, phi) = ((1.0 - u1 * u1).max(0.0).sqrt(), TAU * u2);
+
+                let sample = tangent * (radius * phi.cos()) + bitangent * (radius * phi.sin()) + axis * u1;
+                (sample, sample)
+            }
+        };
+
+        // surfaces seen at grazing angles emit less toward the point, a sphere
+        // is weighted like the disk it looks like from afar, whose cosine is
+        // twice the average one of the visible half
+        let target = self.local_to_global_point(&sample);
+        let normal = self.local_to_global_normal(&normal).normalized();
+
+        let cosine = (point - target).normalized().dot(&normal).max(0.0);
+        let weight = match self.shape {
+            Shape::Sphere => cosine * 2.0,
+            Shape::Rectangle | Shape::Disk => cosine,
+        };
+
+        Some((target, weight))
+    }
+}
@@ -1,16 +1,20 @@
 pub mod directional_light;
 pub mod point_light;
+pub mod area_light;
+pub mod spot_light;
 
 use crate::material::Color;
 use crate::object::Movable;
+use area_light::{AreaLight, Shape};
 use crate::math::{
     point::Point,
-    ray::Ray
+    ray::Ray,
+    transform::Placement,
 };
 
-use serde::{Deserialize, Deserializer, de::{Visitor, Error, MapAccess}};
+use serde::{Deserialize, Deserializer, de::{Visitor, Error, MapAccess, Unexpected}};
 
-pub trait Light: Movable {
+pub trait Light: Movable + Send + Sync {
     fn vec_from_light(&self, point: &Point) -> Point {
         let vec = self.local_to_global_vector(&self.global_to_local_point(point));
         vec.normalized()
@@ -42,14 +46,47 @@ pub trait Light: Movable {
 
     fn illuminate(&self, point: &Point) -> bool;
 
+    /// Fraction of the light power received at **point**
+    fn intensity(&self, _point: &Point) -> f32 {
+        1.0
+    }
+
+    /// Number of shadow rays averaged for each shaded point
+    fn shadow_rays(&self) -> usize {
+        1
+    }
+
+    /// ### Brief
+    /// Pick a point of the light surface seen from **point**
+    ///
+    /// ### Params
+    /// **u1** **u2** Uniform numbers in [0, 1)
+    ///
+    /// ### Return
+    /// The world space position of the sample along the fraction of the
+    /// light emitted toward **point**, `None` for lights without surface
+    fn sample(&self, _point: &Point, _u1: f32, _u2: f32) -> Option<(Point, f32)> {
+        None
+    }
+
     fn diffuse(&self) -> Color;
     fn specular(&self) -> Color;
 }
 
+/// Fields read by the lights of type **light_type**, the others are rejected
+fn light_fields(light_type: &str) -> &'static [&'static str] {
+    match light_type {
+        "DIRECTIONAL" => &["type", "color", "transform", "rotate", "scale", "shear", "matrix", "transforms"],
+        "POINT" => &["type", "color", "intensity", "attenuation", "transform", "rotate", "scale", "shear", "matrix", "transforms"],
+        "SPOT" => &["type", "color", "intensity", "attenuation", "inner", "outer", "transform", "rotate", "scale", "shear", "matrix", "transforms"],
+        _ => &["type", "color", "samples", "transform", "rotate", "scale", "shear", "matrix", "transforms"],
+    }
+}
+
 impl<'de> Deserialize<'de> for Box<dyn Light> {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error> where D: Deserializer<'de> {
-        const FIELDS: &[&str] = &["type", "color", "transform", "rotate", "scale"];
-        const TYPES: &[&str] = &["DIRECTIONAL", "POINT"];
+        const FIELDS: &[&str] = &["type", "color", "intensity", "attenuation", "samples", "inner", "outer", "transform", "rotate", "scale", "shear", "matrix", "transforms"];
+        const TYPES: &[&str] = &["DIRECTIONAL", "POINT", "SPOT", "RECTANGLE", "DISK", "SPHERE"];
         struct LightVisitor;
 
         impl<'de> Visitor<'de> for LightVisitor {
@@ -98,43 +135,85 @@ impl<'de> Deserialize<'de> for Box<dyn Light> {
 
                 let mut light_type = None;
                 let mut color = None;
-                let mut transform = None;
-                let mut rotate = None;
-                let mut scale = None;
+                let mut intensity = None;
+                let mut attenuation = None;
+                let mut samples = None;
+                let mut inner = None;
+                let mut outer = None;
+                let mut placement = Placement::default();
 
                 while let Some(field) = map.next_key()? {
                     match field {
                         "type" => light_type = Some(map.next_value()?),
                         "color" => color = Some(map.next_value()?),
-                        "transform" => transform = Some(map.next_value()?),
-                        "rotate" => rotate = Some(map.next_value()?),
-                        "scale" => scale = Some(map.next_value()?),
-                        _ => return Err(Error::unknown_field(field, FIELDS)),
+                        "intensity" => {
+                            let value: f32 = map.next_value()?;
+                            intensity = Some(value);
+                            if value < 0.0 {
+                                return Err(Error::invalid_value(Unexpected::Float(value as f64), &"a positive intensity"));
+                            }
+                        }
+                        "attenuation" => attenuation = Some(map.next_value()?),
+                        "samples" => {
+                            let value = map.next_value()?;
+                            samples = Some(value);
+                            if value == 0 {
+                                return Err(Error::invalid_value(Unexpected::Unsigned(0), &"at least one shadow ray"));
+                            }
+                        }
+                        "inner" => inner = Some(map.next_value()?),
+                        "outer" => outer = Some(map.next_value()?),
+                        _ => if !placement.visit_field(field, &mut map)? {
+                            return Err(Error::unknown_field(field, FIELDS));
+                        }
                     }
                 }
 
                 let light_type = light_type.ok_or_else(|| Error::missing_field("type"))?;
                 let LightColor(diffuse, specular) = color.ok_or_else(|| Error::missing_field("color"))?;
 
+                let given = [
+                    ("intensity", intensity.is_some()),
+                    ("attenuation", attenuation.is_some()),
+                    ("samples", samples.is_some()),
+                    ("inner", inner.is_some()),
+                    ("outer", outer.is_some()),
+                ];
+
+                for (field, given) in given {
+                    if given && !light_fields(light_type).contains(&field) {
+                        return Err(Error::unknown_field(field, light_fields(light_type)));
+                    }
+                }
+
+                let samples = samples.unwrap_or(16);
+                let intensity = intensity.unwrap_or(1.0);
+                let attenuation = attenuation.unwrap_or(point_light::Attenuation::NONE);
+
                 let mut light: Box<dyn Light> = match light_type {
                     "DIRECTIONAL" => Box::new(directional_light::DirectionalLight::new(diffuse, specular)),
-                    "POINT" => Box::new(point_light::PointLight::new(diffuse, specular)),
-                    _ => return Err(Error::unknown_variant(light_type, TYPES)),
-                };
+                    "POINT" => Box::new(point_light::PointLight::new(diffuse, specular, intensity, attenuation)),
+                    "SPOT" => {
+                        let outer: f32 = outer.unwrap_or(30.0);
+                        let inner: f32 = inner.unwrap_or(outer * 0.75);
 
-                if let Some(Point {x, y , z}) = transform {
-                    light.move_global(x, y, z);
-                }
+                        if !(0.0..90.0).contains(&outer) {
+                            return Err(Error::invalid_value(Unexpected::Float(outer as f64), &"an outer angle in [0, 90)"));
+                        }
 
-                if let Some(Point {x, y , z}) = rotate {
-                    light.rotate_x(x);
-                    light.rotate_y(y);
-                    light.rotate_z(z);
-                }
+                        if !(0.0..=outer).contains(&inner) {
+                            return Err(Error::invalid_value(Unexpected::Float(inner as f64), &"an inner angle in [0, outer]"));
+                        }
 
-                if let Some(scale) = scale {
-                    light.scale(scale);
-                }
+                        Box::new(spot_light::SpotLight::new(diffuse, specular, intensity, attenuation, inner, outer))
+                    }
+                    "RECTANGLE" => Box::new(AreaLight::new(Shape::Rectangle, diffuse, specular, samples)),
+                    "DISK" => Box::new(AreaLight::new(Shape::Disk, diffuse, specular, samples)),
+                    "SPHERE" => Box::new(AreaLight::new(Shape::Sphere, diffuse, specular, samples)),
+                    _ => return Err(Error::unknown_variant(light_type, TYPES)),
+                };
+
+                light.transform_global(&placement.matrix().map_err(Error::custom)?);
 
                 Ok(light)
             }
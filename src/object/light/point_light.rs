@@ -5,22 +5,52 @@ use crate::object::{
     Movable,
 };
 
+use serde::{Deserialize, Deserializer, de::{Visitor, Error, MapAccess}};
 use rulinalg::matrix::Matrix;
 
+/// Falloff of the light with the distance **d**, `1 / (constant + linear * d + quadratic * d²)`
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Attenuation {
+    pub constant: f32,
+    pub linear: f32,
+    pub quadratic: f32,
+}
+
+impl Attenuation {
+    /// Same power at every distance
+    pub const NONE: Attenuation = Attenuation { constant: 1.0, linear: 0.0, quadratic: 0.0 };
+    /// Physically based falloff of a light spreading over a sphere
+    pub const INVERSE_SQUARE: Attenuation = Attenuation { constant: 0.0, linear: 0.0, quadratic: 1.0 };
+
+    pub fn factor(&self, distance: f32) -> f32 {
+        1.0 / (self.constant + self.linear * distance + self.quadratic * distance * distance)
+    }
+}
+
 pub struct PointLight {
     tra: Matrix<f32>,
     inv: Matrix<f32>,
 
     diffuse: Color,
     specular: Color,
+
+    intensity: f32,
+    attenuation: Attenuation,
 }
 
 impl PointLight {
-    pub fn new(diffuse: Color, specular: Color) -> Self {
+    /// ### Brief
+    /// Build a point light
+    ///
+    /// ### Params
+    /// **intensity** Scale of the light power
+    /// **attenuation** Falloff of the light with the distance
+    pub fn new(diffuse: Color, specular: Color, intensity: f32, attenuation: Attenuation) -> Self {
         Self {
             tra: Matrix::identity(4),
             inv: Matrix::identity(4),
-            diffuse, specular
+            diffuse, specular,
+            intensity, attenuation,
         }
     }
 }
@@ -48,6 +78,11 @@ impl Light for PointLight {
         true
     }
 
+    fn intensity(&self, point: &Point) -> f32 {
+        let distance = (self.local_to_global_point(&Point::default()) - *point).norm();
+        self.intensity * self.attenuation.factor(distance)
+    }
+
     fn diffuse(&self) -> Color {
         self.diffuse
     }
@@ -56,3 +91,54 @@ impl Light for PointLight {
         self.specular
     }
 }
+
+impl<'de> Deserialize<'de> for Attenuation {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error> where D: Deserializer<'de> {
+        const FIELDS: &[&str] = &["constant", "linear", "quadratic"];
+        const VARIANTS: &[&str] = &["NONE", "INVERSE_SQUARE"];
+        struct AttenuationVisitor;
+
+        impl<'de> Visitor<'de> for AttenuationVisitor {
+            type Value = Attenuation;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                formatter.write_str("NONE, INVERSE_SQUARE or Attenuation struct")
+            }
+
+            fn visit_str<E>(self, value: &str) -> Result<Self::Value, E> where E: Error {
+                match value {
+                    "NONE" => Ok(Attenuation::NONE),
+                    "INVERSE_SQUARE" => Ok(Attenuation::INVERSE_SQUARE),
+                    _ => Err(Error::unknown_variant(value, VARIANTS)),
+                }
+            }
+
+            fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error> where A: MapAccess<'de> {
+                let mut constant = 0.0;
+                let mut linear = 0.0;
+                let mut quadratic = 0.0;
+
+                while let Some(field) = map.next_key()? {
+                    match field {
+                        "constant" => constant = map.next_value()?,
+                        "linear" => linear = map.next_value()?,
+                        "quadratic" => quadratic = map.next_value()?,
+                        _ => return Err(Error::unknown_field(field, FIELDS)),
+                    }
+                }
+
+                if constant < 0.0 || linear < 0.0 || quadratic < 0.0 {
+                    return Err(Error::custom("attenuation coefficients can't be negative"));
+                }
+
+                if constant + linear + quadratic <= 0.0 {
+                    return Err(Error::custom("at least one attenuation coefficient must be positive"));
+                }
+
+                Ok(Attenuation { constant, linear, quadratic })
+            }
+        }
+
+        deserializer.deserialize_any(AttenuationVisitor)
+    }
+}
@@ -0,0 +1,97 @@
+use crate::math::point::Point;
+use crate::material::Color;
+use crate::object::{
+    light::{Light, point_light::Attenuation},
+    Movable,
+};
+
+use rulinalg::matrix::Matrix;
+
+/// Point light restricted to a cone around its local z axis
+pub struct SpotLight {
+    tra: Matrix<f32>,
+    inv: Matrix<f32>,
+
+    diffuse: Color,
+    specular: Color,
+
+    intensity: f32,
+    attenuation: Attenuation,
+
+    /// Cosine of the half angle under which the light is at full power
+    cos_inner: f32,
+    /// Cosine of the half angle beyond which the light is off
+    cos_outer: f32,
+}
+
+impl SpotLight {
+    /// ### Brief
+    /// Build a spot light
+    ///
+    /// ### Params
+    /// **intensity** Scale of the light power
+    /// **attenuation** Falloff of the light with the distance
+    /// **inner** Half angle of the fully lit cone, in degrees
+    /// **outer** Half angle of the cone where the light fades out, in degrees
+    pub fn new(diffuse: Color, specular: Color, intensity: f32, attenuation: Attenuation, inner: f32, outer: f32) -> Self {
+        Self {
+            tra: Matrix::identity(4),
+            inv: Matrix::identity(4),
+            diffuse, specular,
+            intensity, attenuation,
+            cos_inner: inner.to_radians().cos(),
+            cos_outer: outer.to_radians().cos(),
+        }
+    }
+
+    /// Cosine of the angle between the axis of the spot and **point**
+    fn cos_angle(&self, point: &Point) -> f32 {
+        let local = self.global_to_local_point(point);
+        local.z / local.norm()
+    }
+}
+
+impl Movable for SpotLight {
+    fn tra(&self) -> &Matrix<f32> {
+        &self.tra
+    }
+
+    fn tra_mut(&mut self) -> &mut Matrix<f32> {
+        &mut self.tra
+    }
+
+    fn inv(&self) -> &Matrix<f32> {
+        &self.inv
+    }
+
+    fn inv_mut(&mut self) -> &mut Matrix<f32> {
+        &mut self.inv
+    }
+}
+
+impl Light for SpotLight {
+    fn illuminate(&self, point: &Point) -> bool {
+        self.cos_angle(point) > self.cos_outer
+    }
+
+    fn intensity(&self, point: &Point) -> f32 {
+        let distance = (self.local_to_global_point(&Point::default()) - *point).norm();
+        let power = self.intensity * self.attenuation.factor(distance);
+
+        if self.cos_inner <= self.cos_outer {
+            return power;
+        }
+
+        // smoothstep between the outer and the inner cone
+        let x = ((self.cos_angle(point) - self.cos_outer) / (self.cos_inner - self.cos_outer)).clamp(0.0, 1.0);
+        power * x * x * (3.0 - 2.0 * x)
+    }
+
+    fn diffuse(&self) -> Color {
+        self.diffuse
+    }
+
+    fn specular(&self) -> Color {
+        self.specular
+    }
+}
@@ -0,0 +1,261 @@
+use crate::material::{MatProvider, Material, Color, simple_mat::SimpleMat, texture::Texture};
+use crate::object::{Movable, Object, hit::Hit};
+use crate::bvh::Bvh;
+use crate::stats;
+use crate::math::{
+    bounding_box::BoundingBox,
+    point::Point,
+    ray::Ray
+};
+
+use rulinalg::matrix::Matrix;
+use std::{error::Error, path::{Path, PathBuf}};
+
+const EPSILON: f32 = 1e-6;
+
+struct Triangle {
+    vertices: [Point; 3],
+    normals: Option<[Point; 3]>,
+    uvs: [(f32, f32); 3],
+    material: Option<usize>,
+}
+
+impl Triangle {
+    fn geometric_normal(&self) -> Point {
+        let [a, b, c] = &self.vertices;
+        (b - a).cross(&(c - a)).normalized()
+    }
+
+    /// Möller–Trumbore intersection, returns the ray coefficient
+    /// and the barycentric coordinates of the impact
+    fn intersect(&self, ray: &Ray) -> Option<(f32, [f32; 3])> {
+        let [a, b, c] = &self.vertices;
+        let (edge_1, edge_2) = (b - a, c - a);
+        let vector = ray.vector();
+
+        let p = vector.cross(&edge_2);
+        let det = edge_1.dot(&p);
+        if det.abs() < EPSILON {
+            return None;
+        }
+
+        let inv_det = 1.0 / det;
+        let s = ray.origin() - a;
+        let u = s.dot(&p) * inv_det;
+        if !(0.0..=1.0).contains(&u) {
+            return None;
+        }
+
+        let q = s.cross(&edge_1);
+        let v = vector.dot(&q) * inv_det;
+        if v < 0.0 || u + v > 1.0 {
+            return None;
+        }
+
+        let coef = edge_2.dot(&q) * inv_det;
+        if coef > EPSILON { Some((coef, [1.0 - u - v, u, v])) } else { None }
+    }
+
+    fn shading_normal(&self, bary: &[f32; 3]) -> Point {
+        match &self.normals {
+            Some([n0, n1, n2]) => (n0 * bary[0] + n1 * bary[1] + n2 * bary[2]).normalized(),
+            None => self.geometric_normal(),
+        }
+    }
+
+    fn uv(&self, bary: &[f32; 3]) -> (f32, f32) {
+        let [(u0, v0), (u1, v1), (u2, v2)] = self.uvs;
+        let u = u0 * bary[0] + u1 * bary[1] + u2 * bary[2];
+        let v = v0 * bary[0] + v1 * bary[1] + v2 * bary[2];
+
+        // OBJ texture origin is bottom left while MatProvider expect top left
+        (u.rem_euclid(1.0), (1.0 - v).rem_euclid(1.0))
+    }
+}
+
+pub struct Mesh {
+    tra: Matrix<f32>,
+    inv: Matrix<f32>,
+
+    triangles: Vec<Triangle>,
+    bvh: Bvh,
+
+    materials: Vec<Box<dyn MatProvider>>,
+    mat: Option<Box<dyn MatProvider>>,
+    coef_refraction: f32,
+}
+
+impl Mesh {
+    /// ### Brief
+    /// Load a triangle mesh from the Wavefront OBJ file **file_name**
+    ///
+    /// ### Params
+    /// **file_name** OBJ file, its `.mtl` libraries are resolved from the same folder
+    /// **mat** Material used in place of the `.mtl` ones when provided
+    /// **coef_refraction** Refraction coefficient of the whole mesh
+    pub fn load(file_name: &str, mat: Option<Box<dyn MatProvider>>, coef_refraction: f32) -> Result<Self, Box<dyn Error>> {
+        let (models, materials) = tobj::load_obj(file_name, &tobj::GPU_LOAD_OPTIONS)?;
+        let folder = Path::new(file_name).parent().unwrap_or_else(|| Path::new(""));
+
+        let materials = match (&mat, materials) {
+            (None, Ok(materials)) => materials.iter().map(|m| Self::convert_material(m, folder)).collect::<Result<_, _>>()?,
+            (None, Err(e)) => {
+                let library = Self::material_library(file_name, folder)
+                    .map_or_else(|| "material library".to_owned(), |file| file.display().to_string());
+                return Err(format!("{}: {}", library, e).into());
+            }
+            // the materials of the file are replaced, they don't have to load
+            (Some(_), _) => Vec::new(),
+        };
+
+        let mut triangles = Vec::new();
+        for model in models {
+            let mesh = model.mesh;
+
+            let position = |id: usize| Point::new(
+                mesh.positions[id * 3],
+                mesh.positions[id * 3 + 1],
+                mesh.positions[id * 3 + 2],
+            );
+
+            let normal = |id: usize| Point::new(
+                mesh.normals[id * 3],
+                mesh.normals[id * 3 + 1],
+                mesh.normals[id * 3 + 2],
+            ).normalized();
+
+            let uv = |id: usize| match mesh.texcoords.is_empty() {
+                true => (0.0, 0.0),
+                false => (mesh.texcoords[id * 2], mesh.texcoords[id * 2 + 1]),
+            };
+
+            let material = mesh.material_id.filter(|id| *id < materials.len());
+
+            for face in mesh.indices.chunks_exact(3) {
+                let [a, b, c] = [face[0] as usize, face[1] as usize, face[2] as usize];
+
+                let normals = match mesh.normals.is_empty() {
+                    true => None,
+                    false => Some([normal(a), normal(b), normal(c)]),
+                };
+
+                triangles.push(Triangle {
+                    vertices: [position(a), position(b), position(c)],
+                    uvs: [uv(a), uv(b), uv(c)],
+                    normals, material,
+                });
+            }
+        }
+
+        let boxes: Vec<_> = triangles.iter()
+            .map(|triangle| Some(BoundingBox::from_points(triangle.vertices.iter())))
+            .collect();
+        let bvh = Bvh::new(&boxes);
+
+        Ok(Self {
+            tra: Matrix::identity(4),
+            inv: Matrix::identity(4),
+            triangles, bvh,
+            materials, mat,
+            coef_refraction,
+        })
+    }
+
+    /// Path of the `.mtl` library named by the OBJ file **file_name**, if any
+    fn material_library(file_name: &str, folder: &Path) -> Option<PathBuf> {
+        let content = std::fs::read_to_string(file_name).ok()?;
+
+        content.lines()
+            .find_map(|line| line.trim().strip_prefix("mtllib"))
+            .map(|library| folder.join(library.trim()))
+    }
+
+    fn convert_material(material: &tobj::Material, folder: &Path) -> Result<Box<dyn MatProvider>, String> {
+        let color = |[r, g, b]: [f32; 3]| Color::new(r, g, b);
+
+        // illumination models 3 to 7 enable ray traced reflection
+        let reflection = match material.illumination_model {
+            Some(3..=7) => {
+                let [r, g, b] = material.specular;
+                ((r + g + b) / 3.0 * 255.0).clamp(0.0, 255.0) as u8
+            }
+            _ => 0,
+        };
+
+        let shininess = if material.shininess > 0.0 { material.shininess } else { 50.0 };
+
+        if !material.diffuse_texture.is_empty() {
+            let file = folder.join(&material.diffuse_texture);
+            let texture = Texture::new(&file.to_string_lossy(), 1, 1, reflection, shininess)
+                .map_err(|e| format!("{}: {}", file.display(), e))?;
+            return Ok(Box::new(texture));
+        }
+
+        Ok(Box::new(SimpleMat::new(Material::new(
+            color(material.ambient),
+            color(material.diffuse),
+            color(material.specular),
+            (material.dissolve * 255.0).clamp(0.0, 255.0) as u8,
+            reflection,
+            shininess,
+        ))))
+    }
+}
+
+impl Movable for Mesh {
+    fn tra(&self) -> &Matrix<f32> {
+        &self.tra
+    }
+
+    fn tra_mut(&mut self) -> &mut Matrix<f32> {
+        &mut self.tra
+    }
+
+    fn inv(&self) -> &Matrix<f32> {
+        &self.inv
+    }
+
+    fn inv_mut(&mut self) -> &mut Matrix<f32> {
+        &mut self.inv
+    }
+}
+
+impl Object for Mesh {
+    fn intersect(&self, ray: &Ray) -> Option<Hit<'_>> {
+        let local_ray = self.global_to_local_ray(ray);
+        let (id, (t, bary)) = self.bvh.closer(&local_ray, |id| {
+            stats::record(|stats| stats.intersection_tests += 1);
+            self.triangles[id].intersect(&local_ray).map(|(t, bary)| (t, (t, bary)))
+        })?;
+
+        let triangle = &self.triangles[id];
+        let local = local_ray.origin() + local_ray.vector() * t;
+        let normal = self.local_to_global_normal(&triangle.shading_normal(&bary));
+
+        // the interpolated normal shades, the flat one tells the side and offsets the next rays
+        let mut hit = Hit::new(self, ray, t, local, normal, triangle.uv(&bary));
+        hit.geometric_normal = self.local_to_global_normal(&triangle.geometric_normal()).normalized();
+        hit.front_face = ray.vector().dot(&hit.geometric_normal) < 0.0;
+        hit.primitive = id;
+
+        Some(hit)
+    }
+
+    fn material_at(&self, hit: &Hit) -> Material {
+        let (x, y) = hit.uv;
+
+        match (&self.mat, self.triangles[hit.primitive].material) {
+            (Some(mat), _) => mat.material(x, y),
+            (None, Some(id)) => self.materials[id].material(x, y),
+            (None, None) => Material::default(),
+        }
+    }
+
+    fn coef_refraction(&self) -> f32 {
+        self.coef_refraction
+    }
+
+    fn bounding_box(&self) -> Option<BoundingBox> {
+        self.bvh.bounds().map(|bbox| bbox.transform(self.tra()))
+    }
+}
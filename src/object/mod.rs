@@ -2,15 +2,27 @@ pub mod sphere;
 pub mod camera;
 pub mod square;
 pub mod plane;
+pub mod cylinder;
+pub mod cone;
+pub mod disk;
+pub mod cube;
+pub mod torus;
+pub mod mesh;
+pub mod group;
+pub mod instance;
 pub mod light;
+pub mod hit;
 
 use crate::material::Material;
+use crate::object::hit::Hit;
 use crate::math::{
+    bounding_box::BoundingBox,
     point::Point,
-    ray::Ray
+    ray::Ray,
+    transform::Placement,
 };
 
-use serde::{Deserialize, Deserializer, de::{Visitor, Error, MapAccess}};
+use serde::{Deserialize, Deserializer, de::{Visitor, Error, MapAccess, Unexpected}};
 use rulinalg::matrix::Matrix;
 
 const GAP: f32 = 0.0005;
@@ -40,6 +52,20 @@ pub trait Movable {
         vec.into_vec()
     }
 
+    /// ### Brief
+    /// Bring the normal **normal** of the object space in the world space,
+    /// by the inverse-transpose so it stays orthogonal to scaled or sheared
+    /// surfaces, the result isn't normalized
+    fn local_to_global_normal(&self, normal: &Point) -> Point {
+        let inv = self.inv();
+
+        Point::new(
+            inv[[0, 0]] * normal.x + inv[[1, 0]] * normal.y + inv[[2, 0]] * normal.z,
+            inv[[0, 1]] * normal.x + inv[[1, 1]] * normal.y + inv[[2, 1]] * normal.z,
+            inv[[0, 2]] * normal.x + inv[[1, 2]] * normal.y + inv[[2, 2]] * normal.z,
+        )
+    }
+
     fn global_to_local_ray(&self, ray: &Ray) -> Ray {
         let origin = self.global_to_local_point(ray.origin());
         let vector = self.global_to_local_vector(ray.vector());
@@ -112,37 +138,60 @@ pub trait Movable {
         *self.inv_mut() = self.tra().clone().inverse().unwrap();
     }
 
-    fn scale(&mut self, scale: f32) {
+    fn scale(&mut self, x: f32, y: f32, z: f32) {
         let mat = Matrix::new(4, 4, vec![
-            scale, 0., 0., 0.,
-            0., scale, 0., 0.,
-            0., 0., scale, 0.,
+            x, 0., 0., 0.,
+            0., y, 0., 0.,
+            0., 0., z, 0.,
             0., 0., 0., 1.
         ]);
 
         *self.tra_mut() = self.tra() * mat;
         *self.inv_mut() = self.tra().clone().inverse().unwrap();
     }
+
+    /// ### Brief
+    /// Apply the affine matrix **mat** after the current transform, in the world space
+    fn transform_global(&mut self, mat: &Matrix<f32>) {
+        *self.tra_mut() = mat * self.tra();
+        *self.inv_mut() = self.tra().clone().inverse().unwrap();
+    }
 }
 
-pub trait Object: Movable {
-    fn intersect(&self, ray: &Ray) -> Option<Point>;
-    fn normal(&self, at: &Point, observer: &Point) -> Ray;
-    fn material_at(&self, impact: &Point) -> Material;
-    fn outter_normal(&self, impact: &Point) -> Point;
+pub trait Object: Movable + Send + Sync {
+    fn intersect(&self, ray: &Ray) -> Option<Hit<'_>>;
+    fn material_at(&self, hit: &Hit) -> Material;
     fn coef_refraction(&self) -> f32;
 
-    fn reflected_ray(&self, ray: &Ray, impact: &Point) -> Ray {
-        let normal = self.normal(impact, ray.origin());
+    /// World space box enclosing the object, `None` when it is unbounded
+    fn bounding_box(&self) -> Option<BoundingBox>;
+
+    /// ### Brief
+    /// Link the instances of the object to the definitions they name,
+    /// nothing to do for objects without instances
+    ///
+    /// ### Return
+    /// The name of a definition missing from **definitions**
+    fn resolve(&mut self, _definitions: &instance::Definitions) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// ### Brief
+    /// Build the bounding volume hierarchies of the objects gathered by
+    /// the object, once its instances are resolved, nothing to do for the others
+    fn build_bvh(&mut self) {}
+
+    fn reflected_ray(&self, ray: &Ray, hit: &Hit) -> Ray {
+        let normal = hit.facing_normal();
 
-        let dot = ray.vector().dot(normal.vector());
-        let reflected = ray.vector() - normal.vector() * 2.0 * dot;
+        let dot = ray.vector().dot(&normal);
+        let reflected = ray.vector() - normal * 2.0 * dot;
 
-        Ray::new(impact + reflected * GAP, reflected)
+        Ray::new(hit.position + reflected * GAP, reflected)
     }
 
-    fn refracted_ray(&self, ray: &Ray, impact: &Point) -> Ray {
-        let mut normal = self.outter_normal(impact);
+    fn refracted_ray(&self, ray: &Ray, hit: &Hit) -> Ray {
+        let mut normal = hit.normal;
 
         let mut cosi = ray.vector().dot(&normal);
         let eta = if cosi < 0.0 {
@@ -160,14 +209,37 @@ pub trait Object: Movable {
             ray.vector() * eta + normal * (eta * cosi - k.sqrt())
         };
 
-        Ray::new(impact + refracted * GAP, refracted)
+        Ray::new(hit.position + refracted * GAP, refracted)
+    }
+}
+
+/// Fields read by the objects of type **obj_type**, the others are rejected
+fn object_fields(obj_type: &str) -> &'static [&'static str] {
+    match obj_type {
+        "CYLINDER" | "CONE" => &["type", "material", "refraction", "capped", "transform", "rotate", "scale", "shear", "matrix", "transforms"],
+        "TORUS" => &["type", "material", "refraction", "major", "minor", "transform", "rotate", "scale", "shear", "matrix", "transforms"],
+        "GROUP" => &["type", "children", "transform", "rotate", "scale", "shear", "matrix", "transforms"],
+        "MESH" => &["type", "material", "refraction", "file", "transform", "rotate", "scale", "shear", "matrix", "transforms"],
+        "INSTANCE" => &["type", "material", "refraction", "name", "transform", "rotate", "scale", "shear", "matrix", "transforms"],
+        _ => &["type", "material", "refraction", "transform", "rotate", "scale", "shear", "matrix", "transforms"],
+    }
+}
+
+/// ### Brief
+/// Reject **field** when the objects of type **obj_type** don't read it
+fn check_field<E: Error>(obj_type: &str, field: &str) -> Result<(), E> {
+    match (obj_type, field) {
+        ("GROUP", "material") => Err(E::custom("a group has no material, give one to each child")),
+        ("GROUP", "refraction") => Err(E::custom("a group has no refraction, give one to each child")),
+        _ if !object_fields(obj_type).contains(&field) => Err(E::unknown_field(field, object_fields(obj_type))),
+        _ => Ok(()),
     }
 }
 
 impl<'de> Deserialize<'de> for Box<dyn Object> {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error> where D: Deserializer<'de> {
-        const FIELDS: &[&str] = &["type", "material", "refraction", "transform", "rotate", "scale"];
-        const TYPES: &[&str] = &["SPHERE", "PLANE", "SQUARE"];
+        const FIELDS: &[&str] = &["type", "material", "refraction", "capped", "major", "minor", "file", "children", "name", "transform", "rotate", "scale", "shear", "matrix", "transforms"];
+        const TYPES: &[&str] = &["SPHERE", "PLANE", "SQUARE", "CYLINDER", "CONE", "DISK", "BOX", "TORUS", "MESH", "GROUP", "INSTANCE"];
         struct ObjectVisitor;
 
         impl<'de> Visitor<'de> for ObjectVisitor {
@@ -181,46 +253,121 @@ impl<'de> Deserialize<'de> for Box<dyn Object> {
                 let mut obj_type = None;
                 let mut material = None;
                 let mut refraction = None;
-                let mut transform = None;
-                let mut rotate = None;
-                let mut scale = None;
+                let mut capped = None;
+                let mut major: Option<f32> = None;
+                let mut minor: Option<f32> = None;
+                let mut file: Option<&str> = None;
+                let mut children: Option<Vec<Box<dyn Object>>> = None;
+                let mut name: Option<&str> = None;
+                let mut placement = Placement::default();
 
                 while let Some(field) = map.next_key()? {
+                    // once the type is known, the value of a field it doesn't read,
+                    // like the children of a sphere, isn't even loaded
+                    if let Some(obj_type) = obj_type {
+                        check_field(obj_type, field)?;
+                    }
+
                     match field {
-                        "type" => obj_type = Some(map.next_value()?),
+                        "type" => {
+                            let value = map.next_value()?;
+                            if !TYPES.contains(&value) {
+                                return Err(Error::unknown_variant(value, TYPES));
+                            }
+                            obj_type = Some(value);
+                        }
                         "material" => material = Some(map.next_value()?),
                         "refraction" => refraction = Some(map.next_value()?),
-                        "transform" => transform = Some(map.next_value()?),
-                        "rotate" => rotate = Some(map.next_value()?),
-                        "scale" => scale = Some(map.next_value()?),
-                        _ => return Err(Error::unknown_field(field, FIELDS)),
+                        "capped" => capped = Some(map.next_value()?),
+                        "major" => major = Some(map.next_value()?),
+                        "minor" => minor = Some(map.next_value()?),
+                        "file" => file = Some(map.next_value()?),
+                        "children" => children = Some(map.next_value()?),
+                        "name" => name = Some(map.next_value()?),
+                        _ => if !placement.visit_field(field, &mut map)? {
+                            return Err(Error::unknown_field(field, FIELDS));
+                        }
                     }
                 }
 
                 let obj_type = obj_type.ok_or_else(|| Error::missing_field("type"))?;
-                let material = material.ok_or_else(|| Error::missing_field("material"))?;
-                let coef_refraction = refraction.unwrap_or(1.0);
-
-                let mut object: Box<dyn Object> = match obj_type {
-                    "SPHERE" => Box::new(sphere::Sphere::new(material, coef_refraction)),
-                    "PLANE" => Box::new(plane::Plane::new(material, coef_refraction)),
-                    "SQUARE" => Box::new(square::Square::new(material, coef_refraction)),
-                    _ => return Err(Error::unknown_variant(obj_type, TYPES)),
-                };
 
-                if let Some(Point {x, y , z}) = transform {
-                    object.move_global(x, y, z);
+                let given = [
+                    ("capped", capped.is_some()),
+                    ("major", major.is_some()),
+                    ("minor", minor.is_some()),
+                    ("children", children.is_some()),
+                    ("file", file.is_some()),
+                    ("name", name.is_some()),
+                    ("material", material.is_some()),
+                    ("refraction", refraction.is_some()),
+                ];
+
+                for (field, given) in given {
+                    if given {
+                        check_field(obj_type, field)?;
+                    }
                 }
+                if obj_type == "INSTANCE" {
+                    let name = name.ok_or_else(|| Error::missing_field("name"))?;
+                    // the hits of an instance without material report the shared object,
+                    // whose own refraction is used
+                    if refraction.is_some() && material.is_none() {
+                        return Err(Error::custom("an instance only overrides its refraction along with its material"));
+                    }
 
-                if let Some(Point {x, y , z}) = rotate {
-                    object.rotate_x(x);
-                    object.rotate_y(y);
-                    object.rotate_z(z);
+                    let mut object = instance::Instance::new(name, material, refraction);
+                    object.transform_global(&placement.matrix().map_err(Error::custom)?);
+                    return Ok(Box::new(object));
                 }
 
-                if let Some(scale) = scale {
-                    object.scale(scale);
-                }
+                let coef_refraction = refraction.unwrap_or(1.0);
+                let capped = capped.unwrap_or(true);
+
+                let mut object: Box<dyn Object> = match obj_type {
+                    "MESH" => {
+                        let file = file.ok_or_else(|| Error::missing_field("file"))?;
+                        let mesh = mesh::Mesh::load(file, material, coef_refraction)
+                            .map_err(|e| Error::custom(format!("{}: {}", file, e)))?;
+                        Box::new(mesh)
+                    }
+                    "GROUP" => {
+                        let children = children.ok_or_else(|| Error::missing_field("children"))?;
+                        if children.is_empty() {
+                            return Err(Error::invalid_length(0, &"at least one child"));
+                        }
+
+                        Box::new(group::Group::new(children))
+                    }
+                    _ => {
+                        let material = material.ok_or_else(|| Error::missing_field("material"))?;
+
+                        match obj_type {
+                            "SPHERE" => Box::new(sphere::Sphere::new(material, coef_refraction)),
+                            "PLANE" => Box::new(plane::Plane::new(material, coef_refraction)),
+                            "SQUARE" => Box::new(square::Square::new(material, coef_refraction)),
+                            "CYLINDER" => Box::new(cylinder::Cylinder::new(material, coef_refraction, capped)),
+                            "CONE" => Box::new(cone::Cone::new(material, coef_refraction, capped)),
+                            "DISK" => Box::new(disk::Disk::new(material, coef_refraction)),
+                            "BOX" => Box::new(cube::Cube::new(material, coef_refraction)),
+                            "TORUS" => {
+                                let major = major.unwrap_or(1.0);
+                                let minor = minor.unwrap_or(0.25);
+
+                                for radius in [major, minor] {
+                                    if radius <= 0.0 {
+                                        return Err(Error::invalid_value(Unexpected::Float(radius as f64), &"a strictly positive radius"));
+                                    }
+                                }
+
+                                Box::new(torus::Torus::new(material, coef_refraction, major, minor))
+                            }
+                            _ => return Err(Error::unknown_variant(obj_type, TYPES)),
+                        }
+                    }
+                };
+
+                object.transform_global(&placement.matrix().map_err(Error::custom)?);
 
                 Ok(object)
             }
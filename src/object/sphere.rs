@@ -1,6 +1,7 @@
 use crate::material::{MatProvider, Material};
-use crate::object::{Movable, Object};
+use crate::object::{Movable, Object, hit::Hit};
 use crate::math::{
+    bounding_box::BoundingBox,
     point::Point,
     ray::Ray
 };
@@ -47,9 +48,9 @@ impl Movable for Sphere {
 }
 
 impl Object for Sphere {
-    fn intersect(&self, ray: &Ray) -> Option<Point> {
-        let ray = self.global_to_local_ray(ray);
-        let (origin, vector) = ray.consume();
+    fn intersect(&self, ray: &Ray) -> Option<Hit<'_>> {
+        let local_ray = self.global_to_local_ray(ray);
+        let (origin, vector) = local_ray.consume();
 
         let a = vector.x * vector.x + vector.y * vector.y + vector.z * vector.z;
         let b = 2.0 * (vector.x * origin.x + vector.y * origin.y + vector.z * origin.z);
@@ -61,48 +62,37 @@ impl Object for Sphere {
             let x1 = (-b - d_sqrt) / (2.0 * a);
             let x2 = (-b + d_sqrt) / (2.0 * a);
 
-            let imp = if x1 < 0. && x2 < 0. {
+            let t = if x1 < 0. && x2 < 0. {
                 None
             } else if x1 < x2 && x1 >= 0. {
-                Some(origin + vector * x1)
+                Some(x1)
             } else {
-                Some(origin + vector * x2)
+                Some(x2)
             }?;
 
-            Some(self.local_to_global_point(&imp))
-        } else {
-            None
-        }
-    }
+            let local = origin + vector * t;
+            let normal = self.local_to_global_normal(&local);
 
-    fn normal(&self, at: &Point, observer: &Point) -> Ray {
-        let local = self.global_to_local_point(at);
-        let observer = self.global_to_local_point(observer);
+            let x = local.z.atan2(local.x) / TAU + 0.5;
+            let y = local.y.clamp(-1.0, 1.0).acos() / PI;
 
-        let ray = if observer.norm() > 1.0 {
-            Ray::new(local, local)
+            Some(Hit::new(self, ray, t, local, normal, (x, y)))
         } else {
-            Ray::new(local, -local)
-        };
-
-        self.local_to_global_ray(&ray).normalized()
+            None
+        }
     }
 
-    fn material_at(&self, impact: &Point) -> Material {
-        let impact = self.global_to_local_point(impact);
-
-        let x = impact.z.atan2(impact.x) / TAU + 0.5;
-        let y = impact.y.acos() / PI;
-
+    fn material_at(&self, hit: &Hit) -> Material {
+        let (x, y) = hit.uv;
         self.mat.material(x, y)
     }
 
-    fn outter_normal(&self, impact: &Point) -> Point {
-        let observer = Point::default();
-        -self.normal(impact, &self.local_to_global_point(&observer)).vector()
-    }
-
     fn coef_refraction(&self) -> f32 {
         self.coef_refraction
     }
+
+    fn bounding_box(&self) -> Option<BoundingBox> {
+        let bbox = BoundingBox::new(Point::new(-1.0, -1.0, -1.0), Point::new(1.0, 1.0, 1.0));
+        Some(bbox.transform(self.tra()))
+    }
 }
@@ -1,6 +1,7 @@
 use crate::material::{MatProvider, Material};
-use crate::object::{Movable, Object};
+use crate::object::{Movable, Object, hit::Hit};
 use crate::math::{
+    bounding_box::BoundingBox,
     point::Point,
     ray::Ray
 };
@@ -45,43 +46,34 @@ impl Movable for Square {
 }
 
 impl Object for Square {
-    fn intersect(&self, ray: &Ray) -> Option<Point> {
-        let ray = self.global_to_local_ray(ray);
+    fn intersect(&self, ray: &Ray) -> Option<Hit<'_>> {
+        let local_ray = self.global_to_local_ray(ray);
 
-        let coef = -ray.origin().z / ray.vector().z;
-        let local_impact = ray.origin() + ray.vector() * coef;
+        let t = -local_ray.origin().z / local_ray.vector().z;
+        let local = local_ray.origin() + local_ray.vector() * t;
 
-        if coef > 0.0 && local_impact.x.abs() <= 1.0 && local_impact.y.abs() <= 1.0 {
-            Some(self.local_to_global_point(&local_impact))
+        if t > 0.0 && local.x.abs() <= 1.0 && local.y.abs() <= 1.0 {
+            let x = (if local.x > 0.0 { 0.0 } else { 1.0 } + local.x % 1.0).abs();
+            let y = (if local.y < 0.0 { 0.0 } else { 1.0 } - local.y % 1.0).abs();
+            let normal = self.local_to_global_normal(&Point::new(0.0, 0.0, 1.0));
+
+            Some(Hit::new(self, ray, t, local, normal, (x, y)))
         } else {
             None
         }
     }
 
-    fn normal(&self, at: &Point, observer: &Point) -> Ray {
-        let local_obs = self.global_to_local_point(observer);
-
-        self.local_to_global_ray(
-            &Ray::new(*at, Point::new(0.0, 0.0, local_obs.z))
-        ).normalized()
-    }
-
-    fn material_at(&self, impact: &Point) -> Material {
-        let local = self.global_to_local_point(impact);
-
-        let x = (if local.x > 0.0 { 0.0 } else { 1.0 } + local.x % 1.0).abs();
-        let y = (if local.y < 0.0 { 0.0 } else { 1.0 } - local.y % 1.0).abs();
-
+    fn material_at(&self, hit: &Hit) -> Material {
+        let (x, y) = hit.uv;
         self.mat.material(x, y)
     }
 
-    fn outter_normal(&self, impact: &Point) -> Point {
-        let observer = Point::new(0.0, 0.0, 1.0);
-        let (_origin, vector) = self.normal(impact, &self.local_to_global_point(&observer)).consume();
-        vector
-    }
-
     fn coef_refraction(&self) -> f32 {
         self.coef_refraction
     }
+
+    fn bounding_box(&self) -> Option<BoundingBox> {
+        let bbox = BoundingBox::new(Point::new(-1.0, -1.0, 0.0), Point::new(1.0, 1.0, 0.0));
+        Some(bbox.transform(self.tra()))
+    }
 }
@@ -0,0 +1,123 @@
+use crate::material::{MatProvider, Material};
+use crate::object::{Movable, Object, hit::Hit};
+use crate::math::{
+    bounding_box::BoundingBox,
+    point::Point,
+    ray::Ray,
+    roots::quartic,
+};
+
+use rulinalg::matrix::Matrix;
+use std::f32::consts::TAU;
+
+/// Ring around the y axis, its tube centered on the circle of radius
+/// **major** of the plane y = 0
+pub struct Torus {
+    tra: Matrix<f32>,
+    inv: Matrix<f32>,
+
+    mat: Box<dyn MatProvider>,
+    coef_refraction: f32,
+    /// Distance from the center to the middle of the tube
+    major: f32,
+    /// Radius of the tube
+    minor: f32,
+}
+
+impl Torus {
+    pub fn new(mat: Box<dyn MatProvider>, coef_refraction: f32, major: f32, minor: f32) -> Self {
+        Self {
+            tra: Matrix::identity(4),
+            inv: Matrix::identity(4),
+            coef_refraction,
+            mat, major, minor,
+        }
+    }
+
+    fn local_bounds(&self) -> BoundingBox {
+        let outer = self.major + self.minor;
+        BoundingBox::new(Point::new(-outer, -self.minor, -outer), Point::new(outer, self.minor, outer))
+    }
+}
+
+impl Movable for Torus {
+    fn tra(&self) -> &Matrix<f32> {
+        &self.tra
+    }
+
+    fn tra_mut(&mut self) -> &mut Matrix<f32> {
+        &mut self.tra
+    }
+
+    fn inv(&self) -> &Matrix<f32> {
+        &self.inv
+    }
+
+    fn inv_mut(&mut self) -> &mut Matrix<f32> {
+        &mut self.inv
+    }
+}
+
+impl Object for Torus {
+    fn intersect(&self, ray: &Ray) -> Option<Hit<'_>> {
+        let local_ray = self.global_to_local_ray(ray);
+        let (near, _far) = self.local_bounds().intersect(&local_ray)?;
+
+        // solved from the entry in the box with a unit direction, so the
+        // coefficients of the quartic stay in a sane range
+        let start = near.max(0.0);
+        let length = local_ray.vector().norm();
+        let origin = local_ray.origin() + local_ray.vector() * start;
+        let vector = local_ray.vector() / length;
+
+        let (o, v) = (
+            [origin.x as f64, origin.y as f64, origin.z as f64],
+            [vector.x as f64, vector.y as f64, vector.z as f64],
+        );
+        let (major, minor) = (self.major as f64 * self.major as f64, self.minor as f64 * self.minor as f64);
+
+        // (|p|² + R² - r²)² = 4 R² (x² + z²), with p = o + s v and |v| = 1
+        let od = o[0] * v[0] + o[1] * v[1] + o[2] * v[2];
+        let k = o[0] * o[0] + o[1] * o[1] + o[2] * o[2] + major - minor;
+
+        let (roots, count) = quartic(
+            1.0,
+            4.0 * od,
+            2.0 * k + 4.0 * od * od - 4.0 * major * (v[0] * v[0] + v[2] * v[2]),
+            4.0 * k * od - 8.0 * major * (o[0] * v[0] + o[2] * v[2]),
+            k * k - 4.0 * major * (o[0] * o[0] + o[2] * o[2]),
+        );
+
+        let t = roots[..count].iter()
+            .map(|s| start + *s as f32 / length)
+            .find(|t| *t > 0.0)?;
+
+        let local = local_ray.origin() + local_ray.vector() * t;
+        let ring = (local.x * local.x + local.z * local.z).sqrt();
+
+        // gradient of the implicit equation
+        let sum = local.x * local.x + local.y * local.y + local.z * local.z;
+        let side = sum - self.major * self.major - self.minor * self.minor;
+        let normal = Point::new(local.x * side, local.y * (side + 2.0 * self.major * self.major), local.z * side);
+        let normal = self.local_to_global_normal(&normal);
+
+        // u goes around the ring like on a sphere, v around the tube
+        let u = local.z.atan2(local.x) / TAU + 0.5;
+        let v = local.y.atan2(ring - self.major) / TAU + 0.5;
+
+        Some(Hit::new(self, ray, t, local, normal, (u, v)))
+    }
+
+    fn material_at(&self, hit: &Hit) -> Material {
+        let (x, y) = hit.uv;
+        self.mat.material(x, y)
+    }
+
+    fn coef_refraction(&self) -> f32 {
+        self.coef_refraction
+    }
+
+    fn bounding_box(&self) -> Option<BoundingBox> {
+        Some(self.local_bounds().transform(self.tra()))
+    }
+}
@@ -0,0 +1,385 @@
+use crate::material::Color;
+use crate::math::point::Point;
+
+use exr::prelude::{AnyChannel, AnyChannels, FlatSamples, ImageAttributes, IntegerBounds, Layer, LayerAttributes, Layers, WritableImage};
+use image::{ImageBuffer, Rgb, RgbImage, codecs::hdr::HdrEncoder};
+use std::{error::Error, fs::File, io::BufWriter, path::Path};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ToneMapping {
+    Clamp,
+    Reinhard,
+    Aces,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Encoding {
+    Linear,
+    Srgb,
+}
+
+/// Conversion of the scene radiance to the displayed values
+#[derive(Clone, Copy, Debug)]
+pub struct OutputTransform {
+    /// Exposure correction in stops, each stop doubles the radiance
+    pub exposure: f32,
+    pub tone_mapping: ToneMapping,
+    pub encoding: Encoding,
+}
+
+impl Default for OutputTransform {
+    fn default() -> Self {
+        Self {
+            exposure: 0.0,
+            tone_mapping: ToneMapping::Clamp,
+            encoding: Encoding::Linear,
+        }
+    }
+}
+
+impl OutputTransform {
+    /// ### Brief
+    /// Bring the radiance **color** to the [0, 1] display range
+    pub fn apply(&self, color: Color) -> Color {
+        let color = color * self.exposure.exp2();
+
+        let [red, green, blue] = color.to_array().map(|channel| {
+            let channel = match self.tone_mapping {
+                ToneMapping::Clamp => channel,
+                ToneMapping::Reinhard => channel / (1.0 + channel),
+                ToneMapping::Aces => aces(channel),
+            }.clamp(0.0, 1.0);
+
+            match self.encoding {
+                Encoding::Linear => channel,
+                Encoding::Srgb => srgb(channel),
+            }
+        });
+
+        Color::new(red, green, blue)
+    }
+}
+
+/// Krzysztof Narkowicz fit of the ACES filmic curve
+fn aces(x: f32) -> f32 {
+    (x * (2.51 * x + 0.03)) / (x * (2.43 * x + 0.59) + 0.14)
+}
+
+/// sRGB transfer function of a linear value in [0, 1]
+fn srgb(x: f32) -> f32 {
+    if x <= 0.003_130_8 {
+        x * 12.92
+    } else {
+        1.055 * x.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+/// Extra image rendered along the beauty one
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Pass {
+    Depth,
+    Normal,
+    Albedo,
+    ObjectId,
+    Direct,
+    Indirect,
+}
+
+impl Pass {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Pass::Depth => "depth",
+            Pass::Normal => "normal",
+            Pass::Albedo => "albedo",
+            Pass::ObjectId => "object_id",
+            Pass::Direct => "direct",
+            Pass::Indirect => "indirect",
+        }
+    }
+
+    fn channels(&self) -> &'static [&'static str] {
+        match self {
+            Pass::Depth => &["Z"],
+            Pass::Normal => &["X", "Y", "Z"],
+            Pass::ObjectId => &["id"],
+            Pass::Albedo | Pass::Direct | Pass::Indirect => &["R", "G", "B"],
+        }
+    }
+
+    /// Copy the channels of the pass from **sample** to the pixel **data**
+    fn extract(&self, sample: &Sample, data: &mut [f32]) {
+        match self {
+            Pass::Depth => data[0] = sample.depth,
+            Pass::Normal => data.copy_from_slice(&[sample.normal.x, sample.normal.y, sample.normal.z]),
+            Pass::ObjectId => data[0] = sample.object.map_or(-1.0, |id| id as f32),
+            Pass::Albedo => data.copy_from_slice(&sample.albedo.to_array()),
+            Pass::Direct => data.copy_from_slice(&sample.direct.to_array()),
+            Pass::Indirect => data.copy_from_slice(&sample.indirect.to_array()),
+        }
+    }
+
+    /// ### Brief
+    /// Turn the raw **data** of the pass into viewable colors
+    /// for the formats without arbitrary channels
+    fn display(&self, data: &[f32]) -> Vec<Color> {
+        match self {
+            Pass::Depth => {
+                // the far end is taken before the last few percents, so a
+                // receding ground plane doesn't crush every other depth
+                let mut finite: Vec<f32> = data.iter().copied().filter(|depth| depth.is_finite()).collect();
+                finite.sort_unstable_by(f32::total_cmp);
+                let far = finite.get(finite.len() * 95 / 100).copied().unwrap_or(0.0);
+
+                data.iter().map(|depth| match depth.is_finite() && far > 0.0 {
+                    true => Color::new_gray((depth / far).min(1.0)),
+                    false => Color::WHITE,
+                }).collect()
+            }
+            Pass::Normal => data.chunks_exact(3)
+                .map(|n| Color::new(n[0] * 0.5 + 0.5, n[1] * 0.5 + 0.5, n[2] * 0.5 + 0.5))
+                .collect(),
+            Pass::ObjectId => data.iter().map(|id| match *id >= 0.0 {
+                // spread consecutive ids on distinct hues
+                true => {
+                    let hue = (*id * 0.618_034).fract() * 6.0;
+                    let ramp = |offset: f32| ((hue + offset) % 6.0 - 3.0).abs().clamp(1.0, 2.0) - 1.0;
+                    Color::new(ramp(0.0), ramp(4.0), ramp(2.0))
+                }
+                false => Color::BLACK,
+            }).collect(),
+            Pass::Albedo | Pass::Direct | Pass::Indirect => data.chunks_exact(3)
+                .map(|c| Color::new(c[0], c[1], c[2]))
+                .collect(),
+        }
+    }
+}
+
+/// Everything learned from the rays shot through a pixel
+#[derive(Clone, Copy)]
+pub struct Sample {
+    pub beauty: Color,
+    /// Light coming straight from the light sources
+    pub direct: Color,
+    /// Ambient, reflected, refracted or bounced light
+    pub indirect: Color,
+    /// Diffuse color of the first surface hit
+    pub albedo: Color,
+    /// World space normal of the first surface hit, facing the camera
+    pub normal: Point,
+    /// Camera space depth of the first surface hit, infinite when nothing was hit
+    pub depth: f32,
+    /// Index in the scene of the first object hit
+    pub object: Option<usize>,
+}
+
+impl Sample {
+    pub fn background(color: Color) -> Self {
+        Self {
+            beauty: color,
+            direct: Color::default(),
+            indirect: Color::default(),
+            albedo: Color::default(),
+            normal: Point::default(),
+            depth: f32::INFINITY,
+            object: None,
+        }
+    }
+
+    /// Colors multiplied by **coef**, the geometric data are left untouched
+    pub fn scaled(mut self, coef: f32) -> Self {
+        self.beauty = self.beauty * coef;
+        self.direct = self.direct * coef;
+        self.indirect = self.indirect * coef;
+        self.albedo = self.albedo * coef;
+        self
+    }
+
+    /// ### Brief
+    /// Divide the accumulated colors by the sum of the filter weights,
+    /// the negative lobes of some filters can't leave negative light
+    pub fn resolved(self, total_weight: f32) -> Self {
+        let positive = |color: Color| Color::new(color.red.max(0.0), color.green.max(0.0), color.blue.max(0.0));
+        let mut sample = self.scaled(1.0 / total_weight);
+
+        sample.beauty = positive(sample.beauty);
+        sample.direct = positive(sample.direct);
+        sample.indirect = positive(sample.indirect);
+        sample.albedo = positive(sample.albedo);
+        sample
+    }
+
+    /// ### Brief
+    /// Add the colors of **other** weighted by **coef**,
+    /// the geometric data of the first sample are kept
+    pub fn accumulate(&mut self, other: &Sample, coef: f32) {
+        self.beauty += other.beauty * coef;
+        self.direct += other.direct * coef;
+        self.indirect += other.indirect * coef;
+        self.albedo += other.albedo * coef;
+    }
+}
+
+/// Beauty image along the requested passes
+pub struct Framebuffer {
+    width: usize,
+    height: usize,
+    beauty: Vec<Color>,
+    passes: Vec<(Pass, Vec<f32>)>,
+}
+
+impl Framebuffer {
+    pub fn new(width: usize, height: usize, passes: &[Pass]) -> Self {
+        Self {
+            width, height,
+            beauty: vec![Color::default(); width * height],
+            passes: passes.iter().map(|pass| (*pass, vec![0.0; width * height * pass.channels().len()])).collect(),
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Linear radiance of every pixel, row by row
+    pub fn beauty(&self) -> &[Color] {
+        &self.beauty
+    }
+
+    /// Raw channels of **pass**, interleaved pixel by pixel, `None` when it wasn't rendered
+    pub fn pass(&self, pass: Pass) -> Option<&[f32]> {
+        self.passes.iter().find(|(id, _)| *id == pass).map(|(_, data)| data.as_slice())
+    }
+
+    /// ### Brief
+    /// Beauty image converted to 8 bits colors by **transform**
+    pub fn to_rgb8(&self, transform: &OutputTransform) -> RgbImage {
+        RgbImage::from_fn(self.width as u32, self.height as u32, |x, y| {
+            Rgb(transform.apply(self.beauty[y as usize * self.width + x as usize]).to_rgb8())
+        })
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, sample: &Sample) {
+        let id = y * self.width + x;
+        self.beauty[id] = sample.beauty;
+
+        for (pass, data) in self.passes.iter_mut() {
+            let channels = pass.channels().len();
+            pass.extract(sample, &mut data[id * channels..(id + 1) * channels]);
+        }
+    }
+
+    /// ### Brief
+    /// Write the framebuffer in **file_name**, the format is chosen from the extension
+    ///
+    /// An `.exr` file holds every passes as layers, other formats get
+    /// one more file per pass, named `<file_name>.<pass>.<extension>`
+    pub fn write(&self, file_name: &str, transform: &OutputTransform, bit_depth: u8) -> Result<(), Box<dyn Error>> {
+        let (width, height) = (self.width, self.height);
+        let path = Path::new(file_name);
+
+        if extension(path).as_deref() == Some("exr") {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+
+            let beauty: Vec<f32> = self.beauty.iter().flat_map(|pix| pix.to_array()).collect();
+            let mut layers = vec![exr_layer("beauty", &["R", "G", "B"], &beauty, width, height)];
+
+            for (pass, data) in self.passes.iter() {
+                layers.push(exr_layer(pass.name(), pass.channels(), data, width, height));
+            }
+
+            let attributes = ImageAttributes::new(IntegerBounds::from_dimensions((width, height)));
+            exr::prelude::Image::from_layers(attributes, Layers::from_vec(layers)).write().to_file(path)?;
+            return Ok(());
+        }
+
+        write_image(file_name, width, height, &self.beauty, transform, bit_depth)?;
+
+        for (pass, data) in self.passes.iter() {
+            let file = match (path.file_stem(), path.extension()) {
+                (Some(stem), Some(ext)) => path.with_file_name(format!(
+                    "{}.{}.{}", stem.to_string_lossy(), pass.name(), ext.to_string_lossy()
+                )),
+                _ => path.with_file_name(format!("{}.{}", file_name, pass.name())),
+            };
+
+            let transform = match pass {
+                Pass::Direct | Pass::Indirect => *transform,
+                _ => OutputTransform::default(),
+            };
+
+            write_image(&file.to_string_lossy(), width, height, &pass.display(data), &transform, bit_depth)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn extension(path: &Path) -> Option<String> {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_ascii_lowercase())
+}
+
+fn exr_layer(name: &str, channels: &[&str], data: &[f32], width: usize, height: usize) -> Layer<AnyChannels<FlatSamples>> {
+    let count = channels.len();
+    let channels = channels.iter().enumerate().map(|(id, channel)| {
+        let samples = data.iter().skip(id).step_by(count).copied().collect();
+        AnyChannel::new(*channel, FlatSamples::F32(samples))
+    }).collect();
+
+    Layer::new(
+        (width, height),
+        LayerAttributes::named(name),
+        exr::prelude::Encoding::FAST_LOSSLESS,
+        AnyChannels::sort(channels),
+    )
+}
+
+/// ### Brief
+/// Write the radiance **pixels** in **file_name**, the format is chosen from the extension
+///
+/// `.exr` and `.hdr` keep the linear radiance untouched, other formats go through
+/// **transform** and are quantized to **bit_depth** bits when the format allows it
+pub fn write_image(file_name: &str, width: usize, height: usize, pixels: &[Color], transform: &OutputTransform, bit_depth: u8) -> Result<(), Box<dyn Error>> {
+    let path = Path::new(file_name);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    match extension(path).as_deref() {
+        Some("exr") => {
+            let beauty: Vec<f32> = pixels.iter().flat_map(|pix| pix.to_array()).collect();
+            let layer = exr_layer("beauty", &["R", "G", "B"], &beauty, width, height);
+
+            exr::prelude::Image::from_layer(layer).write().to_file(path)?;
+        }
+        Some("hdr") => {
+            let pixels: Vec<_> = pixels.iter().map(|pix| Rgb(pix.to_array())).collect();
+            let file = BufWriter::new(File::create(path)?);
+
+            HdrEncoder::new(file).encode(&pixels, width, height)?;
+        }
+        Some("png") if bit_depth == 16 => {
+            let buf = pixels.iter()
+                .flat_map(|pix| transform.apply(*pix).to_array())
+                .map(|channel| (channel * u16::MAX as f32).round() as u16)
+                .collect();
+
+            let image: ImageBuffer<Rgb<u16>, Vec<u16>> = ImageBuffer::from_raw(width as u32, height as u32, buf)
+                .ok_or("pixel count doesn't match the image size")?;
+            image.save(path)?;
+        }
+        _ => {
+            let buf: Vec<u8> = pixels.iter().flat_map(|pix| transform.apply(*pix).to_rgb8()).collect();
+            image::save_buffer(path, buf.as_slice(), width as u32, height as u32, image::ColorType::Rgb8)?;
+        }
+    }
+
+    Ok(())
+}
@@ -1,35 +1,155 @@
-use crate::object::camera::{Camera, Focal};
+use crate::object::{Object, instance, camera::{Camera, Focal}};
+use crate::output::{OutputTransform, ToneMapping, Encoding, Pass};
 use crate::material::Color;
 use crate::scene::Scene;
+use crate::tile::TileOrder;
+use crate::sampler::{Pattern, Filter, FilterKind, Adaptive};
 
-use serde::{Deserialize, Deserializer, de::{Visitor, Error, MapAccess}};
+use serde::{Deserialize, Deserializer, de::{Visitor, Error, MapAccess, Unexpected}};
 
-pub fn parse_file(file_name: &str) -> (Scene, Camera, Config) {
-    let content = std::fs::read_to_string(file_name).unwrap();
-    let Parser { scene, camera, config } = serde_json::from_str(content.as_str()).unwrap();
+/// Reason why a scene file can't be loaded
+#[derive(Debug)]
+pub enum ParseError {
+    /// The file can't be read
+    Io { file: String, source: std::io::Error },
+    /// The file isn't valid JSON or doesn't describe a scene
+    Json {
+        file: String,
+        line: usize,
+        column: usize,
+        /// Key holding the error, like `objects[3].material.mat`
+        path: String,
+        message: String,
+    },
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseError::Io { file, source } => write!(f, "{}: {}", file, source),
+            ParseError::Json { file, line, column, path, message } => {
+                write!(f, "{}:{}:{}: ", file, line, column)?;
+
+                if path != "." {
+                    write!(f, "in `{}`: ", path)?;
+                }
+
+                write!(f, "{}", message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ParseError::Io { source, .. } => Some(source),
+            ParseError::Json { .. } => None,
+        }
+    }
+}
+
+/// ### Brief
+/// Load the scene, camera and render settings described by the JSON file **file_name**
+pub fn parse_file(file_name: &str) -> Result<(Scene, Camera, Config), ParseError> {
+    let content = std::fs::read_to_string(file_name).map_err(|source| ParseError::Io {
+        file: file_name.to_owned(),
+        source,
+    })?;
+
+    let deserializer = &mut serde_json::Deserializer::from_str(content.as_str());
+    let Parser { scene, camera, config } = serde_path_to_error::deserialize(deserializer).map_err(|e| {
+        let path = e.path().to_string();
+        let inner = e.into_inner();
+
+        // serde_json appends the position to its messages, it is already in front
+        let message = inner.to_string();
+        let message = match message.rfind(" at line ") {
+            Some(id) => message[..id].to_owned(),
+            None => message,
+        };
+
+        ParseError::Json {
+            file: file_name.to_owned(),
+            line: inner.line(),
+            column: inner.column(),
+            path, message,
+        }
+    })?;
+
+    Ok((scene, camera, config))
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Acceleration {
+    Bvh,
+    Linear,
+}
 
-    (scene, camera, config)
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Integrator {
+    Whitted,
+    Path,
 }
 
 pub struct Config {
     pub output: String,
     pub threads: usize,
+    /// Side of the square tiles shared between the threads, in pixels
+    pub tile_size: usize,
+    pub tile_order: TileOrder,
     pub depth: usize,
+    pub acceleration: Acceleration,
+    pub integrator: Integrator,
+    /// Samples per pixel
+    pub samples: usize,
+    pub pattern: Pattern,
+    pub filter: Filter,
+    /// More samples for the pixels that need them, `None` keeps the same count everywhere
+    pub adaptive: Option<Adaptive>,
+    pub transform: OutputTransform,
+    pub bit_depth: u8,
+    /// Extra images rendered along the beauty one
+    pub passes: Vec<Pass>,
+}
+
+/// One thread per core, or a single one when the core count is unknown
+fn default_threads() -> usize {
+    std::thread::available_parallelism().map_or(1, |count| count.get())
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
             output: "output.png".to_owned(),
-            threads: 1,
+            threads: default_threads(),
+            tile_size: 32,
+            tile_order: TileOrder::Spiral,
             depth: 0,
+            acceleration: Acceleration::Bvh,
+            integrator: Integrator::Whitted,
+            samples: 1,
+            pattern: Pattern::Regular,
+            filter: Filter::new(FilterKind::Box),
+            adaptive: None,
+            transform: OutputTransform::default(),
+            bit_depth: 8,
+            passes: Vec::new(),
         }
     }
 }
 
 impl<'de> Deserialize<'de> for Config {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error> where D: Deserializer<'de> {
-        const FIELDS: &[&str] = &["output", "threads", "depth"];
+        const FIELDS: &[&str] = &["output", "threads", "tile_size", "tile_order", "depth", "acceleration", "integrator", "samples", "pattern", "filter", "adaptive", "exposure", "tone_mapping", "encoding", "bit_depth", "passes"];
+        const ACCELERATIONS: &[&str] = &["BVH", "LINEAR"];
+        const INTEGRATORS: &[&str] = &["WHITTED", "PATH"];
+        const TONE_MAPPINGS: &[&str] = &["CLAMP", "REINHARD", "ACES"];
+        const ENCODINGS: &[&str] = &["LINEAR", "SRGB"];
+        const TILE_ORDERS: &[&str] = &["SPIRAL", "HILBERT"];
+        const PATTERNS: &[&str] = &["REGULAR", "JITTERED", "HALTON", "SOBOL"];
+        const FILTERS: &[&str] = &["BOX", "TENT", "GAUSSIAN", "MITCHELL"];
+        const PASSES: &[&str] = &["DEPTH", "NORMAL", "ALBEDO", "OBJECT_ID", "DIRECT", "INDIRECT"];
         struct ConfigVisitor;
 
         impl<'de> Visitor<'de> for ConfigVisitor {
@@ -41,19 +161,143 @@ impl<'de> Deserialize<'de> for Config {
 
             fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error> where A: MapAccess<'de> {
                 let mut output = "output.png".to_owned();
-                let mut threads = 1;
-                let mut depth = 0;
+                let mut threads = default_threads();
+                let mut tile_size = 32;
+                let mut tile_order = TileOrder::Spiral;
+                let mut depth = None;
+                let mut acceleration = Acceleration::Bvh;
+                let mut integrator = Integrator::Whitted;
+                let mut samples = None;
+                let mut pattern = None;
+                let mut filter = Filter::new(FilterKind::Box);
+                let mut adaptive = None;
+                let mut transform = OutputTransform::default();
+                let mut bit_depth = 8;
+                let mut passes = Vec::new();
 
                 while let Some(field) = map.next_key()? {
                     match field {
-                        "threads" => threads = map.next_value()?,
+                        "threads" => {
+                            threads = map.next_value()?;
+                            if threads == 0 {
+                                return Err(Error::invalid_value(Unexpected::Unsigned(0), &"at least one thread"));
+                            }
+                        }
+                        "tile_size" => {
+                            tile_size = map.next_value()?;
+                            if tile_size == 0 {
+                                return Err(Error::invalid_value(Unexpected::Unsigned(0), &"a tile of at least one pixel"));
+                            }
+                        }
+                        "tile_order" => {
+                            tile_order = match map.next_value()? {
+                                "SPIRAL" => TileOrder::Spiral,
+                                "HILBERT" => TileOrder::Hilbert,
+                                value => return Err(Error::unknown_variant(value, TILE_ORDERS)),
+                            }
+                        }
                         "output" => output = map.next_value()?,
-                        "depth" => depth = map.next_value()?,
+                        "depth" => depth = Some(map.next_value()?),
+                        "acceleration" => {
+                            acceleration = match map.next_value()? {
+                                "BVH" => Acceleration::Bvh,
+                                "LINEAR" => Acceleration::Linear,
+                                value => return Err(Error::unknown_variant(value, ACCELERATIONS)),
+                            }
+                        }
+                        "integrator" => {
+                            integrator = match map.next_value()? {
+                                "WHITTED" => Integrator::Whitted,
+                                "PATH" => Integrator::Path,
+                                value => return Err(Error::unknown_variant(value, INTEGRATORS)),
+                            }
+                        }
+                        "samples" => samples = Some(map.next_value()?),
+                        "pattern" => {
+                            pattern = Some(match map.next_value()? {
+                                "REGULAR" => Pattern::Regular,
+                                "JITTERED" => Pattern::Jittered,
+                                "HALTON" => Pattern::Halton,
+                                "SOBOL" => Pattern::Sobol,
+                                value => return Err(Error::unknown_variant(value, PATTERNS)),
+                            })
+                        }
+                        "adaptive" => adaptive = Some(map.next_value()?),
+                        "filter" => {
+                            filter = Filter::new(match map.next_value()? {
+                                "BOX" => FilterKind::Box,
+                                "TENT" => FilterKind::Tent,
+                                "GAUSSIAN" => FilterKind::Gaussian,
+                                "MITCHELL" => FilterKind::Mitchell,
+                                value => return Err(Error::unknown_variant(value, FILTERS)),
+                            })
+                        }
+                        "exposure" => transform.exposure = map.next_value()?,
+                        "bit_depth" => {
+                            bit_depth = map.next_value()?;
+                            if bit_depth != 8 && bit_depth != 16 {
+                                return Err(Error::invalid_value(Unexpected::Unsigned(bit_depth as u64), &"8 or 16"));
+                            }
+                        }
+                        "tone_mapping" => {
+                            transform.tone_mapping = match map.next_value()? {
+                                "CLAMP" => ToneMapping::Clamp,
+                                "REINHARD" => ToneMapping::Reinhard,
+                                "ACES" => ToneMapping::Aces,
+                                value => return Err(Error::unknown_variant(value, TONE_MAPPINGS)),
+                            }
+                        }
+                        "encoding" => {
+                            transform.encoding = match map.next_value()? {
+                                "LINEAR" => Encoding::Linear,
+                                "SRGB" => Encoding::Srgb,
+                                value => return Err(Error::unknown_variant(value, ENCODINGS)),
+                            }
+                        }
+                        "passes" => {
+                            for pass in map.next_value::<Vec<&str>>()? {
+                                let pass = match pass {
+                                    "DEPTH" => Pass::Depth,
+                                    "NORMAL" => Pass::Normal,
+                                    "ALBEDO" => Pass::Albedo,
+                                    "OBJECT_ID" => Pass::ObjectId,
+                                    "DIRECT" => Pass::Direct,
+                                    "INDIRECT" => Pass::Indirect,
+                                    value => return Err(Error::unknown_variant(value, PASSES)),
+                                };
+
+                                if !passes.contains(&pass) {
+                                    passes.push(pass);
+                                }
+                            }
+                        }
                         _ => return Err(Error::unknown_field(field, FIELDS)),
                     }
                 }
 
-                Ok(Self::Value { output, threads, depth })
+                // a single path per pixel is mostly noise
+                let samples = samples.unwrap_or(match integrator {
+                    Integrator::Whitted => 1,
+                    Integrator::Path => 16,
+                });
+
+                if samples == 0 {
+                    return Err(Error::invalid_value(Unexpected::Unsigned(0), &"at least one sample per pixel"));
+                }
+
+                // russian roulette ends most paths well before the depth of the path integrator
+                let depth = depth.unwrap_or(match integrator {
+                    Integrator::Whitted => 0,
+                    Integrator::Path => 5,
+                });
+
+                // paths are noisy anyway, random placement hides the grid
+                let pattern = pattern.unwrap_or(match integrator {
+                    Integrator::Whitted => Pattern::Regular,
+                    Integrator::Path => Pattern::Jittered,
+                });
+
+                Ok(Self::Value { output, threads, tile_size, tile_order, depth, acceleration, integrator, samples, pattern, filter, adaptive, transform, bit_depth, passes })
             }
         }
 
@@ -61,6 +305,38 @@ impl<'de> Deserialize<'de> for Config {
     }
 }
 
+/// Named objects only drawn through instances, resolved once the whole file is read
+struct Definitions(Vec<(String, Box<dyn Object>)>);
+
+impl<'de> Deserialize<'de> for Definitions {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error> where D: Deserializer<'de> {
+        struct DefinitionsVisitor;
+
+        impl<'de> Visitor<'de> for DefinitionsVisitor {
+            type Value = Definitions;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                formatter.write_str("map of named objects")
+            }
+
+            fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error> where A: MapAccess<'de> {
+                let mut definitions: Vec<(String, Box<dyn Object>)> = Vec::new();
+
+                while let Some(name) = map.next_key::<String>()? {
+                    if definitions.iter().any(|(other, _)| *other == name) {
+                        return Err(Error::custom(format!("definition `{}` is given twice", name)));
+                    }
+                    definitions.push((name, map.next_value()?));
+                }
+
+                Ok(Definitions(definitions))
+            }
+        }
+
+        deserializer.deserialize_map(DefinitionsVisitor)
+    }
+}
+
 struct Parser {
     scene: Scene,
     camera: Camera,
@@ -69,7 +345,7 @@ struct Parser {
 
 impl<'de> Deserialize<'de> for Parser {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error> where D: Deserializer<'de> {
-        const FIELDS: &[&str] = &["scene", "objects", "lights", "camera", "config"];
+        const FIELDS: &[&str] = &["scene", "definitions", "objects", "lights", "camera", "config"];
         struct ParserVisitor;
 
         impl<'de> Visitor<'de> for ParserVisitor {
@@ -107,7 +383,7 @@ impl<'de> Deserialize<'de> for Parser {
                                 }
 
                                 let backbround = backgroung.unwrap_or(Color::SKY);
-                                let ambient = ambient.unwrap_or_else(|| Color::new_gray(120));
+                                let ambient = ambient.unwrap_or_else(|| Color::from_gray8(120));
                                 Ok(SceneColor(backbround, ambient))
                             }
                         }
@@ -116,7 +392,8 @@ impl<'de> Deserialize<'de> for Parser {
                     }
                 }
 
-                let mut objects = None;
+                let mut objects: Option<Vec<Box<dyn Object>>> = None;
+                let mut definitions = None;
                 let mut lights = None;
                 let mut colors = None;
                 let mut camera = None;
@@ -125,6 +402,7 @@ impl<'de> Deserialize<'de> for Parser {
                 while let Some(field) = map.next_key()? {
                     match field {
                         "scene" => colors = Some(map.next_value()?),
+                        "definitions" => definitions = Some(map.next_value::<Definitions>()?.0),
                         "objects" => objects = Some(map.next_value()?),
                         "lights" => lights = Some(map.next_value()?),
                         "camera" => camera = Some(map.next_value()?),
@@ -133,15 +411,29 @@ impl<'de> Deserialize<'de> for Parser {
                     }
                 }
 
-                let objects = objects.unwrap_or_default();
+                let config: Config = config.unwrap_or_default();
+
+                // instances are linked once every key is read, whatever their order
+                let definitions = instance::Definitions::new(definitions.unwrap_or_default(), config.acceleration)
+                    .map_err(|e| Error::custom(format!("in `definitions`: {}", e)))?;
+
+                let mut objects = objects.unwrap_or_default();
+                for (id, object) in objects.iter_mut().enumerate() {
+                    object.resolve(&definitions)
+                        .map_err(|name| Error::custom(format!("in `objects[{}]`: unknown definition `{}`", id, name)))?;
+                }
+
                 let lights = lights.unwrap_or_default();
                 let SceneColor(background, ambient) = colors.unwrap_or_else(
-                    || SceneColor(Color::SKY, Color::new_gray(120))
+                    || SceneColor(Color::SKY, Color::from_gray8(120))
                 );
 
-                let scene = Scene::new(objects, lights, background, ambient);
+                let mut scene = Scene::new(objects, lights, background, ambient);
                 let camera = camera.unwrap_or_else(|| Camera::new(1920, 1080, Focal::Perspective(1.7)));
-                let config = config.unwrap_or_default();
+
+                if config.acceleration == Acceleration::Bvh {
+                    scene.build_bvh();
+                }
 
                 Ok(Self::Value { scene, camera, config })
             }
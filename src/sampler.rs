@@ -0,0 +1,241 @@
+use crate::math::sampling::{radical_inverse, sobol_2d};
+
+use rand::{Rng, rngs::SmallRng};
+use serde::{Deserialize, Deserializer, de::{Visitor, Error, MapAccess, Unexpected}};
+
+/// Placement of the samples inside a pixel
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Pattern {
+    /// Centers of the cells of a grid
+    Regular,
+    /// One random point in each cell of a grid
+    Jittered,
+    /// Halton sequence in base 2 and 3, randomly shifted for each pixel
+    Halton,
+    /// Sobol sequence, randomly scrambled for each pixel
+    Sobol,
+}
+
+impl Pattern {
+    /// ### Brief
+    /// Random offset of the sequences of a pixel, drawn once and shared by
+    /// all its batches so they keep continuing the same sequence
+    pub fn scramble(&self, rng: &mut SmallRng) -> (u32, u32) {
+        match self {
+            Pattern::Regular | Pattern::Jittered => (0, 0),
+            Pattern::Halton | Pattern::Sobol => (rng.gen(), rng.gen()),
+        }
+    }
+
+    /// ### Brief
+    /// Generate **count** points of the unit square
+    ///
+    /// The grids have `ceil(sqrt(count))` columns, the last row is only
+    /// partially filled when **count** is not a square
+    ///
+    /// ### Params
+    /// **start** Points already generated for the pixel, later batches continue
+    /// the sequences and are jittered on the grids instead of repeating the first one
+    /// **scramble** Offset of the sequences given by `scramble`
+    pub fn points(&self, start: usize, count: usize, scramble: (u32, u32), rng: &mut SmallRng) -> Vec<(f32, f32)> {
+        let columns = (count as f32).sqrt().ceil() as usize;
+        let rows = count.div_ceil(columns.max(1));
+        let cell = |id: usize, (jx, jy): (f32, f32)| {
+            (((id % columns) as f32 + jx) / columns as f32, ((id / columns) as f32 + jy) / rows as f32)
+        };
+
+        match self {
+            Pattern::Regular if start == 0 => (0..count).map(|id| cell(id, (0.5, 0.5))).collect(),
+            Pattern::Regular | Pattern::Jittered => (0..count).map(|id| cell(id, (rng.gen(), rng.gen()))).collect(),
+            Pattern::Halton => {
+                let unit = |bits: u32| bits as f32 / (u32::MAX as f32 + 1.0);
+                let (sx, sy) = (unit(scramble.0), unit(scramble.1));
+
+                (start as u32..(start + count) as u32).map(|id| {
+                    let x = radical_inverse(id + 1, 2) + sx;
+                    let y = radical_inverse(id + 1, 3) + sy;
+                    (x.fract(), y.fract())
+                }).collect()
+            }
+            Pattern::Sobol => (start as u32..(start + count) as u32).map(|id| sobol_2d(id, scramble)).collect(),
+        }
+    }
+}
+
+/// Shape of a pixel filter
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum FilterKind {
+    Box,
+    Tent,
+    Gaussian,
+    /// Mitchell-Netravali with B = C = 1/3, its negative lobes sharpen the edges
+    Mitchell,
+}
+
+/// Number of bins of the tabulated filters
+const FILTER_BINS: usize = 64;
+
+/// Weighting of the samples according to their distance to the pixel center
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Filter {
+    kind: FilterKind,
+    /// Cumulated absolute weights of the positive half of the filter,
+    /// the filter is symmetric
+    cdf: [f32; FILTER_BINS + 1],
+}
+
+impl Filter {
+    /// Tabulate the filter of shape **kind**, once for all the samples
+    pub fn new(kind: FilterKind) -> Self {
+        let mut filter = Self { kind, cdf: [0.0; FILTER_BINS + 1] };
+        let radius = filter.radius();
+
+        for bin in 0..FILTER_BINS {
+            let x = (bin as f32 + 0.5) / FILTER_BINS as f32 * radius;
+            filter.cdf[bin + 1] = filter.cdf[bin] + filter.weight_1d(x).abs();
+        }
+
+        filter
+    }
+
+    pub fn kind(&self) -> FilterKind {
+        self.kind
+    }
+
+    /// Distance to the pixel center beyond which samples are ignored, in pixels
+    pub fn radius(&self) -> f32 {
+        match self.kind {
+            FilterKind::Box => 0.5,
+            FilterKind::Tent => 1.0,
+            FilterKind::Gaussian => 1.5,
+            FilterKind::Mitchell => 2.0,
+        }
+    }
+
+    /// ### Brief
+    /// Move a point of the unit square to an offset from the pixel center,
+    /// the offsets are distributed like the absolute value of the filter
+    ///
+    /// ### Return
+    /// The offset in pixels and the weight of the sample, negative
+    /// in the negative lobes of the filter and 1 everywhere else
+    pub fn sample(&self, u: f32, v: f32) -> ((f32, f32), f32) {
+        let (x, y) = (self.warp(u), self.warp(v));
+        let sign = |value: f32| if value < 0.0 { -1.0 } else { 1.0 };
+
+        ((x, y), sign(self.weight_1d(x)) * sign(self.weight_1d(y)))
+    }
+
+    /// Inverse of the cumulated distribution of the filter on one axis
+    fn warp(&self, u: f32) -> f32 {
+        let radius = self.radius();
+        let t = u * 2.0 - 1.0;
+
+        if self.kind == FilterKind::Box {
+            return t * radius;
+        }
+
+        let cdf = &self.cdf;
+        let target = t.abs() * cdf[FILTER_BINS];
+        let bin = cdf.partition_point(|value| *value <= target).clamp(1, FILTER_BINS) - 1;
+        let width = cdf[bin + 1] - cdf[bin];
+        let fraction = if width > 0.0 { (target - cdf[bin]) / width } else { 0.0 };
+
+        let x = (bin as f32 + fraction) / FILTER_BINS as f32 * radius;
+        x.copysign(t)
+    }
+
+    fn weight_1d(&self, x: f32) -> f32 {
+        let radius = self.radius();
+        let x = x.abs();
+
+        match self.kind {
+            FilterKind::Box => 1.0,
+            FilterKind::Tent => (1.0 - x / radius).max(0.0),
+            FilterKind::Gaussian => {
+                // shifted so the weight reaches zero on the radius
+                const ALPHA: f32 = 2.0;
+                ((-ALPHA * x * x).exp() - (-ALPHA * radius * radius).exp()).max(0.0)
+            }
+            FilterKind::Mitchell => {
+                const B: f32 = 1.0 / 3.0;
+                const C: f32 = 1.0 / 3.0;
+
+                if x < 1.0 {
+                    ((12.0 - 9.0 * B - 6.0 * C) * x * x * x
+                        + (-18.0 + 12.0 * B + 6.0 * C) * x * x
+                        + (6.0 - 2.0 * B)) / 6.0
+                } else if x < 2.0 {
+                    ((-B - 6.0 * C) * x * x * x
+                        + (6.0 * B + 30.0 * C) * x * x
+                        + (-12.0 * B - 48.0 * C) * x
+                        + (8.0 * B + 24.0 * C)) / 6.0
+                } else {
+                    0.0
+                }
+            }
+        }
+    }
+}
+
+/// Refinement of the pixels whose samples disagree
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Adaptive {
+    /// Samples per pixel beyond which the refinement stops
+    pub max_samples: usize,
+    /// Standard error of the pixel luminance under which it is considered converged
+    pub threshold: f32,
+}
+
+impl Adaptive {
+    /// ### Brief
+    /// Tell if the mean of the **luminances** of a pixel samples is known
+    /// precisely enough, a single sample can't be judged
+    pub fn converged(&self, luminances: &[f32]) -> bool {
+        let count = luminances.len();
+        if count < 2 {
+            return false;
+        }
+
+        let mean = luminances.iter().sum::<f32>() / count as f32;
+        let variance = luminances.iter().map(|lum| (lum - mean) * (lum - mean)).sum::<f32>() / (count - 1) as f32;
+
+        (variance / count as f32).sqrt() <= self.threshold
+    }
+}
+
+impl<'de> Deserialize<'de> for Adaptive {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error> where D: Deserializer<'de> {
+        const FIELDS: &[&str] = &["max_samples", "threshold"];
+        struct AdaptiveVisitor;
+
+        impl<'de> Visitor<'de> for AdaptiveVisitor {
+            type Value = Adaptive;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                formatter.write_str("Adaptive struct")
+            }
+
+            fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error> where A: MapAccess<'de> {
+                let mut max_samples = 64;
+                let mut threshold: f32 = 0.01;
+
+                while let Some(field) = map.next_key()? {
+                    match field {
+                        "max_samples" => max_samples = map.next_value()?,
+                        "threshold" => threshold = map.next_value()?,
+                        _ => return Err(Error::unknown_field(field, FIELDS)),
+                    }
+                }
+
+                if threshold <= 0.0 {
+                    return Err(Error::invalid_value(Unexpected::Float(threshold as f64), &"a strictly positive threshold"));
+                }
+
+                Ok(Adaptive { max_samples, threshold })
+            }
+        }
+
+        deserializer.deserialize_map(AdaptiveVisitor)
+    }
+}
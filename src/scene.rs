@@ -1,24 +1,47 @@
 use crate::material::Color;
+use crate::bvh::Bvh;
+use crate::stats;
 use crate::object::{
     light::Light,
+    hit::Hit,
     Object,
 };
 use crate::math::{
+    bounding_box::BoundingBox,
     point::Point,
     ray::Ray,
 };
 
+use std::f32::consts::SQRT_2;
+
+/// Maximum number of transparent surfaces crossed by a ray toward a light
+const MAX_FILTER_DEPTH: usize = 64;
+
 pub struct Scene {
     objects: Vec<Box<dyn Object>>,
     lights: Vec<Box<dyn Light>>,
 
     background: Color,
     ambient: Color,
+
+    bvh: Option<Bvh>,
 }
 
 impl Scene {
     pub fn new(objects: Vec<Box<dyn Object>>, lights: Vec<Box<dyn Light>>, background: Color, ambient: Color) -> Self {
-        Scene { objects, lights, background, ambient }
+        Scene { objects, lights, background, ambient, bvh: None }
+    }
+
+    /// ### Brief
+    /// Build the bounding volume hierarchy used by **closer**, and the ones
+    /// of the groups, without it every object is tested against every ray
+    pub fn build_bvh(&mut self) {
+        for obj in self.objects.iter_mut() {
+            obj.build_bvh();
+        }
+
+        let boxes: Vec<_> = self.objects.iter().map(|obj| obj.bounding_box()).collect();
+        self.bvh = Some(Bvh::new(&boxes));
     }
 
     pub fn background(&self) -> Color {
@@ -29,61 +52,111 @@ impl Scene {
         self.ambient
     }
 
-    pub fn closer(&self, ray: &Ray) -> Option<(&dyn Object, Point)> {
-        let mut hit = None;
-        let mut dist = f32::INFINITY;
+    pub fn closer(&self, ray: &Ray) -> Option<Hit<'_>> {
+        self.closer_id(ray).map(|(_id, hit)| hit)
+    }
 
-        for obj in self.objects.iter() {
-            if let Some(impact) = obj.intersect(ray) {
-                let new_dist = (impact - ray.origin()).norm();
+    /// ### Brief
+    /// Closest impact of **ray** along the index of the object hit
+    pub fn closer_id(&self, ray: &Ray) -> Option<(usize, Hit<'_>)> {
+        if let Some(bvh) = &self.bvh {
+            return bvh.closer(ray, |id| {
+                stats::record(|stats| stats.intersection_tests += 1);
+                self.objects[id].intersect(ray).map(|hit| (hit.t, hit))
+            });
+        }
+
+        let mut closer: Option<(usize, Hit)> = None;
+        stats::record(|stats| stats.intersection_tests += self.objects.len() as u64);
 
-                if new_dist < dist {
-                    dist = new_dist;
-                    hit = Some((obj.as_ref(), impact));
+        for (id, obj) in self.objects.iter().enumerate() {
+            if let Some(hit) = obj.intersect(ray) {
+                if closer.is_none_or(|(_, closer)| hit.t < closer.t) {
+                    closer = Some((id, hit));
                 }
             }
         }
 
-        hit
+        closer
     }
 
-    pub fn light_filter(&self, point: &Point, light: &dyn Light, depth: usize) -> Color {
-        let (mut origin, vector) = light.ray_to_light(point).consume();
+    pub fn light_filter(&self, from: &Hit, light: &dyn Light, depth: usize) -> Color {
+        let (mut origin, vector) = light.ray_to_light(&from.position).consume();
         origin = origin + vector * 0.01;
 
         let ray = Ray::new(origin, vector);
         let closer = self.closer(&ray);
+        stats::record(|stats| stats.shadow_rays += 1);
 
         match closer {
-            None => Color::new_gray(255),
-            Some((object, impact)) => {
-                if point == &impact {
-                    println!("recursive spot at depth {}", depth);
-                    return Color::new_gray(255);
+            None => Color::WHITE,
+            Some(hit) => {
+                if from.position == hit.position {
+                    return Color::WHITE;
                 }
 
-                let dist_light = light.distance(point);
-                let dist_object = (impact - point).norm();
+                // far from the origin the float precision can bounce the ray
+                // between two surfaces forever
+                if depth >= MAX_FILTER_DEPTH {
+                    return Color::WHITE;
+                }
 
-                if dist_light > dist_object {
-                    let material = object.material_at(&impact);
-                    let alpha_coef = material.alpha as f32 / 255.0;
-                    let shadow = Color::new_gray(255) * (1.0 - alpha_coef) -
-                        (Color::new_gray(255) - material.diffuse) * alpha_coef;
+                let dist_light = light.distance(&origin);
 
-                    use std::f32::consts::SQRT_2;
-                    (shadow * SQRT_2) * (self.light_filter(&impact, light, depth + 1) * SQRT_2)
+                if dist_light > hit.t {
+                    self.transmittance(&hit) * (self.light_filter(&hit, light, depth + 1) * SQRT_2).clamped()
                 } else {
-                    Color::new_gray(255)
+                    Color::WHITE
+                }
+            }
+        }
+    }
+
+    /// ### Brief
+    /// Light let through by the objects standing between **from**
+    /// and the point **target**, picked on the surface of an area light
+    pub fn point_filter(&self, from: &Hit, target: &Point, depth: usize) -> Color {
+        let vector = (*target - from.position).normalized();
+        let origin = from.position + vector * 0.01;
+
+        let ray = Ray::new(origin, vector);
+        let closer = self.closer(&ray);
+        stats::record(|stats| stats.shadow_rays += 1);
+
+        match closer {
+            None => Color::WHITE,
+            Some(hit) => {
+                if depth >= MAX_FILTER_DEPTH || (*target - origin).norm() <= hit.t {
+                    return Color::WHITE;
                 }
+
+                self.transmittance(&hit) * (self.point_filter(&hit, target, depth + 1) * SQRT_2).clamped()
             }
         }
     }
 
+    /// Color let through by the surface at **hit**, white when fully transparent
+    fn transmittance(&self, hit: &Hit) -> Color {
+        let material = hit.object.material_at(hit);
+        let alpha_coef = material.alpha as f32 / 255.0;
+        let shadow = Color::WHITE * (1.0 - alpha_coef) -
+            (Color::WHITE - material.diffuse) * alpha_coef;
+
+        (shadow * SQRT_2).clamped()
+    }
+
     pub fn lights(&self) -> &Vec<Box<dyn Light>> {
         &self.lights
     }
-}
 
-unsafe impl Send for Scene {}
-unsafe impl Sync for Scene {}
+    pub fn objects(&self) -> &Vec<Box<dyn Object>> {
+        &self.objects
+    }
+
+    /// Bounding box of every bounded objects, `None` when there is none
+    pub fn bounds(&self) -> Option<BoundingBox> {
+        self.objects.iter()
+            .filter_map(|obj| obj.bounding_box())
+            .reduce(|bounds, bbox| bounds.union(&bbox))
+    }
+}
@@ -0,0 +1,91 @@
+use std::cell::RefCell;
+use std::time::Duration;
+
+/// Counters of the work done while rendering
+#[derive(Default, Clone, Copy, Debug)]
+pub struct Stats {
+    pub primary_rays: u64,
+    pub shadow_rays: u64,
+    /// Mirror reflections, along the diffuse bounces of the paths
+    pub reflection_rays: u64,
+    pub refraction_rays: u64,
+    /// Objects and triangles tested against a ray
+    pub intersection_tests: u64,
+    /// Deepest bounce reached by a ray
+    pub max_depth: usize,
+    /// Time spent rendering
+    pub duration: Duration,
+}
+
+thread_local! {
+    // every thread counts on its own, nothing is shared while rendering
+    static STATS: RefCell<Stats> = RefCell::new(Stats::default());
+}
+
+/// Update the counters of the current thread
+pub fn record<F: FnOnce(&mut Stats)>(update: F) {
+    STATS.with(|stats| update(&mut stats.borrow_mut()));
+}
+
+/// Counters of the current thread, which are reset
+pub fn take() -> Stats {
+    STATS.with(|stats| stats.take())
+}
+
+impl Stats {
+    pub fn total_rays(&self) -> u64 {
+        self.primary_rays + self.shadow_rays + self.reflection_rays + self.refraction_rays
+    }
+
+    pub fn merge(&mut self, other: &Stats) {
+        self.primary_rays += other.primary_rays;
+        self.shadow_rays += other.shadow_rays;
+        self.reflection_rays += other.reflection_rays;
+        self.refraction_rays += other.refraction_rays;
+        self.intersection_tests += other.intersection_tests;
+        self.max_depth = self.max_depth.max(other.max_depth);
+        self.duration = self.duration.max(other.duration);
+    }
+
+    /// Print the counters
+    pub fn report(&self) {
+        let total = self.total_rays();
+        let per_sec = total as f64 / self.duration.as_secs_f64().max(f64::EPSILON);
+        let tests = self.intersection_tests as f64 / total.max(1) as f64;
+
+        println!("primary rays    : {}", self.primary_rays);
+        println!("shadow rays     : {}", self.shadow_rays);
+        println!("reflection rays : {}", self.reflection_rays);
+        println!("refraction rays : {}", self.refraction_rays);
+        println!("rays per second : {:.0}", per_sec);
+        println!("tests per ray   : {:.2}", tests);
+        println!("max depth       : {}", self.max_depth);
+    }
+}
+
+/// State of a render, given each time a tile is done
+#[derive(Clone, Copy, Debug)]
+pub struct Progress {
+    pub done_tiles: usize,
+    pub total_tiles: usize,
+    pub done_pixels: usize,
+    pub total_pixels: usize,
+    /// Time since the render started
+    pub elapsed: Duration,
+}
+
+impl Progress {
+    /// Fraction of the pixels already rendered
+    pub fn ratio(&self) -> f32 {
+        self.done_pixels as f32 / self.total_pixels.max(1) as f32
+    }
+
+    /// Estimated time left, assuming the remaining pixels cost as much as the done ones
+    pub fn eta(&self) -> Duration {
+        let ratio = self.ratio();
+        match ratio > 0.0 {
+            true => self.elapsed.mul_f32(1.0 / ratio - 1.0),
+            false => Duration::ZERO,
+        }
+    }
+}
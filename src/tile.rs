@@ -0,0 +1,96 @@
+/// Rectangle of pixels rendered by a single thread
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Tile {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+/// Order in which the tiles are handed to the threads
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TileOrder {
+    /// From the center of the image to its borders, turning around it
+    Spiral,
+    /// Along a Hilbert curve, neighbouring tiles are rendered close in time
+    Hilbert,
+}
+
+impl TileOrder {
+    /// ### Brief
+    /// Cut an image in tiles and sort them
+    ///
+    /// ### Params
+    /// **width** **height** Size of the image
+    /// **size** Side of the tiles, the ones on the right and bottom borders may be smaller
+    pub fn tiles(&self, width: usize, height: usize, size: usize) -> Vec<Tile> {
+        let (columns, rows) = (width.div_ceil(size), height.div_ceil(size));
+        let tile = |column: usize, row: usize| Tile {
+            x: column * size,
+            y: row * size,
+            width: size.min(width - column * size),
+            height: size.min(height - row * size),
+        };
+
+        match self {
+            TileOrder::Spiral => {
+                let center = ((columns as f32 - 1.0) / 2.0, (rows as f32 - 1.0) / 2.0);
+                let mut cells: Vec<(usize, usize)> = (0..rows)
+                    .flat_map(|row| (0..columns).map(move |column| (column, row)))
+                    .collect();
+
+                // ring by ring, each ring walked by angle
+                let key = |&(column, row): &(usize, usize)| {
+                    let (dx, dy) = (column as f32 - center.0, row as f32 - center.1);
+                    (dx.abs().max(dy.abs()).round(), dy.atan2(dx))
+                };
+
+                cells.sort_by(|a, b| {
+                    let (a, b) = (key(a), key(b));
+                    a.0.total_cmp(&b.0).then(a.1.total_cmp(&b.1))
+                });
+
+                cells.into_iter().map(|(column, row)| tile(column, row)).collect()
+            }
+            TileOrder::Hilbert => {
+                let side = columns.max(rows).next_power_of_two();
+
+                (0..side * side)
+                    .map(|d| hilbert_point(side, d))
+                    .filter(|&(column, row)| column < columns && row < rows)
+                    .map(|(column, row)| tile(column, row))
+                    .collect()
+            }
+        }
+    }
+}
+
+/// ### Brief
+/// Cell at the distance **d** along the Hilbert curve filling a square of **side** cells
+fn hilbert_point(side: usize, d: usize) -> (usize, usize) {
+    let (mut x, mut y) = (0, 0);
+    let mut t = d;
+    let mut s = 1;
+
+    while s < side {
+        let rx = 1 & (t / 2);
+        let ry = 1 & (t ^ rx);
+
+        // rotate the quadrant so the sub curves connect
+        if ry == 0 {
+            if rx == 1 {
+                x = s - 1 - x;
+                y = s - 1 - y;
+            }
+
+            std::mem::swap(&mut x, &mut y);
+        }
+
+        x += s * rx;
+        y += s * ry;
+        t /= 4;
+        s *= 2;
+    }
+
+    (x, y)
+}